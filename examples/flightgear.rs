@@ -47,7 +47,8 @@ struct PropertyTreeLeaf {
     pub value: f64,
 }
 
-const KEYS: &'static [&'static str] = &[
+const KEYS: &[&str] = &[
+    "/velocities/airspeed-kt",
     "/velocities/groundspeed-kt",
     "/velocities/vertical-speed-fps",
     "/position/longitude-deg",
@@ -59,7 +60,7 @@ const KEYS: &'static [&'static str] = &[
     "/orientation/heading-deg",
 ];
 
-const USAGE: &'static str = "usage: <Flightgear base url>";
+const USAGE: &str = "usage: <Flightgear base url>";
 
 // http://localhost:5400/json/velocities?i=y&t=y&d=3
 
@@ -68,7 +69,7 @@ fn main() -> Result<(), Box<dyn Error>> {
         let args: Vec<String> = env::args().collect();
         let base_uri = args.get(1).expect(USAGE);
 
-        let mut taws = Taws::new(Default::default());
+        let mut taws = Taws::new(Default::default()).unwrap();
         let mut fg_stream = new_flightgear_stream(base_uri.as_str()).await?;
         let mut frames: u128 = 0;
 
@@ -81,7 +82,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 
             // Next frame begins
             if ts > aircraft_state.timestamp {
-                let alert_state = taws.process(&aircraft_state);
+                let taws_state = taws.process(&aircraft_state, &[]);
                 print!("{esc}[2J{esc}[1;1H", esc = 27 as char);
                 frames += 1;
                 println!(
@@ -89,11 +90,14 @@ fn main() -> Result<(), Box<dyn Error>> {
                     frames,
                     now.elapsed(),
                 );
-                println!("{}\n{:#?}", aircraft_state, alert_state);
+                println!("{}\n{:#?}", aircraft_state, taws_state);
             }
             aircraft_state.timestamp = ts;
 
             match leaf.path.as_str() {
+                "/velocities/airspeed-kt" => {
+                    aircraft_state.speed_air = Velocity::new::<knot>(leaf.value)
+                }
                 "/velocities/groundspeed-kt" => {
                     aircraft_state.speed_ground = Velocity::new::<knot>(leaf.value)
                 }
@@ -15,4 +15,13 @@ Feature: Alert Priorization
     @MOPS_300
     Scenario: Alert Priority
       Given an alert can occur concurrently
-      Then that alert has a priority
\ No newline at end of file
+      Then that alert has a priority
+
+    Scenario Outline: Installed alert priorities must rank every alert
+      Then a TAWS with alert priorities missing <alert> refuses to start
+
+      Examples:
+        | alert  |
+        | Mode 1 |
+        | Mode 6 |
+        | FFAC   |
\ No newline at end of file
@@ -0,0 +1,40 @@
+Feature: Aural Alerts
+  Alerts are announced to the crew by voice messages. A voice message is completed before the next
+  one starts, unless a warning of higher priority interrupts it. Voice messages of alerts which
+  persist are repeated at an interval.
+
+  Scenario: Altitude callouts are spoken
+    Given Mode 6 is armed
+    And Mode 6 is not inhibited
+    When the aircraft descends from 600 to 400 feet above terrain
+    Then the voice message "FIVE HUNDRED" is played
+    And the voice message "ONE THOUSAND" is not played
+
+  Scenario: Callouts of inhibited alert systems are not spoken
+    Given Mode 6 is inhibited
+    And FFAC is inhibited
+    When the aircraft descends from 600 to 400 feet above terrain
+    Then the voice message "FIVE HUNDRED" is not played
+
+  Scenario: The TAWS does not talk over an external alert of higher priority
+    Given a TCAS RA is active
+    And Mode 6 is armed
+    When the aircraft descends from 600 to 400 feet above terrain
+    Then the voice message "FIVE HUNDRED" is not played
+    And Mode 6 is suppressed
+
+  Scenario: External alerts of lower priority do not suppress the TAWS
+    Given a TCAS TA is active
+    And Mode 6 is armed
+    When the aircraft descends from 600 to 400 feet above terrain
+    Then the voice message "FIVE HUNDRED" is played
+    And Mode 6 is not suppressed
+
+  Scenario: External alerts may bring their own priority
+    Given an external alert of priority 0 is active
+    And Mode 6 is armed
+    When the aircraft descends from 600 to 400 feet above terrain
+    Then the voice message "FIVE HUNDRED" is not played
+    And Mode 6 is suppressed
+
+# vim: set ts=2 sw=2 expandtab: retab: expandtab #
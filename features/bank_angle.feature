@@ -0,0 +1,52 @@
+Feature: Bank Angle: Excessive Bank Angle
+  The bank angle alert is given when the roll angle exceeds a limit which depends on the height
+  above terrain. Close to the ground only small roll angles are allowed, while above 150 foot the
+  caution is given beyond 35 degrees and the warning beyond 45 degrees.
+
+  Scenario: Mode Arming/Disarming
+    Given the plane is flying
+    Then Bank Angle shall be armed
+
+  Scenario: Warning for excessive roll
+    Given Bank Angle is armed
+    And Bank Angle is not inhibited
+    And the height above terrain is greater or equal to 150 foot
+    When the roll angle is at least 46 degrees
+    Then a Bank Angle warning alert is emitted within 1 seconds
+
+  Scenario: Caution for excessive roll
+    Given Bank Angle is armed
+    And Bank Angle is not inhibited
+    And the height above terrain is greater or equal to 150 foot
+    When the roll angle is at least 36 degrees
+    Then a Bank Angle caution alert is emitted within 1 seconds
+
+  Scenario: No alert for moderate roll
+    Given Bank Angle is armed
+    And Bank Angle is not inhibited
+    And the height above terrain is greater or equal to 150 foot
+    When the roll angle is at most 34 degrees
+    Then a Bank Angle caution alert is not emitted at all
+
+  Scenario: Lower limit close to the ground
+    Given Bank Angle is armed
+    And Bank Angle is not inhibited
+    When the height above terrain is between 5 and 30 feet
+    And the roll angle is at least 16 degrees
+    Then a Bank Angle warning alert is emitted within 1 seconds
+
+  Scenario: No alert on the ground
+    Given Bank Angle is armed
+    And Bank Angle is not inhibited
+    When the height above terrain is between 0 and 4 feet
+    And the roll angle is at least 46 degrees
+    Then a Bank Angle caution alert is not emitted at all
+
+  Scenario: No alert when not armed
+    Given Bank Angle is not armed
+    And Bank Angle is not inhibited
+    And the height above terrain is greater or equal to 150 foot
+    When the roll angle is at least 46 degrees
+    Then a Bank Angle caution alert is not emitted at all
+
+# vim: set ts=2 sw=2 expandtab: retab: expandtab #
@@ -16,4 +16,30 @@ Feature: Five Hundred Foot Altitude Callout
     And non-precision approach is selected
     And the height above nearest runway elevation is greater or equal to 500 foot
     When the height above nearest runway elevation is lower than 500 foot
-    Then a FFAC annunciation alert is emitted within 1.3 seconds
\ No newline at end of file
+    Then a FFAC annunciation alert is emitted within 1.3 seconds
+
+  @MOPS_292
+  Scenario: Five hundred foot above terrain without runway data
+    Given no runway data is available
+    And FFAC is armed
+    And FFAC is not inhibited
+    And non-precision approach is selected
+    And the height above terrain is greater or equal to 500 foot
+    When the height above terrain is lower than 500 foot
+    Then a FFAC annunciation alert is emitted within 1.3 seconds
+
+  Scenario: No callout without approach type input
+    Given FFAC is armed
+    And FFAC is not inhibited
+    And the approach type is not available
+    And the height above terrain is greater or equal to 500 foot
+    When the height above terrain is lower than 500 foot
+    Then a FFAC annunciation alert is not emitted at all
+
+  Scenario: No callout without non-precision approach
+    Given FFAC is armed
+    And FFAC is not inhibited
+    And non-precision approach is not selected
+    And the height above terrain is greater or equal to 500 foot
+    When the height above terrain is lower than 500 foot
+    Then a FFAC annunciation alert is not emitted at all
@@ -0,0 +1,59 @@
+Feature: Flight Phase Detection
+  The TAWS derives the flight phase from the history of aircraft states. Functions which are only
+  needed in some phases arm and disarm themselves automatically when the phase changes.
+
+  Scenario: On ground before the first aircraft state
+    Given the plane is flying
+    Then the flight phase is ground
+
+  Scenario: Take-off
+    When the aircraft takes off
+    Then the flight phase is take-off
+
+  Scenario: Climb after take-off
+    When the aircraft takes off
+    And the aircraft climbs from 100 to 1600 feet above terrain
+    Then the flight phase is climb
+
+  Scenario: Approach
+    When the aircraft descends from 3000 to 1000 feet above terrain
+    Then the flight phase is approach
+
+  Scenario: Go around
+    When the aircraft descends from 3000 to 200 feet above terrain
+    And the aircraft climbs from 200 to 500 feet above terrain
+    Then the flight phase is go around
+
+  Scenario: No go around on a brief climb
+    When the aircraft descends from 3000 to 200 feet above terrain
+    And the aircraft climbs from 200 to 250 feet above terrain
+    Then the flight phase is approach
+
+  Scenario: Landing
+    When the aircraft descends from 3000 to 0 feet above terrain
+    Then the flight phase is landing
+
+  @MOPS_007
+  Scenario: Automatic Arming on Take-off
+    Given Mode 3 is not armed
+    And Mode 5 is armed
+    When the aircraft takes off
+    Then Mode 3 shall be armed
+    And Mode 5 shall not be armed
+
+  @MOPS_007
+  Scenario: Automatic Arming on Approach
+    Given PDA is not armed
+    And Mode 5 is not armed
+    When the aircraft descends from 3000 to 1000 feet above terrain
+    Then PDA shall be armed
+    And Mode 5 shall be armed
+
+  @MOPS_007
+  Scenario: Automatic Arming on Go Around
+    Given Mode 3 is not armed
+    When the aircraft descends from 3000 to 200 feet above terrain
+    And the aircraft climbs from 200 to 500 feet above terrain
+    Then Mode 3 shall be armed
+
+# vim: set ts=2 sw=2 expandtab: retab: expandtab #
@@ -0,0 +1,109 @@
+Feature: Mode 2: Excessive Closure Rate to Terrain
+  The Mode 2 alert is intended to generate caution alerts and time-critical
+  warning alerts when the aircraft is closing in on the terrain at a high rate
+  relative to its height above terrain. The closure rate is derived from
+  successive measurements of the height above terrain and filtered against
+  radio altimeter noise. Mode 2A is active while the flaps are not in landing
+  configuration, its upper boundary is extended with increasing airspeed.
+  Mode 2B is active while the flaps are in landing configuration.
+
+  Scenario: Mode Arming/Disarming
+    Given the plane is flying
+    Then Mode 2 shall be armed
+
+  #Rule: Mode 2A Caution Envelope
+
+    Scenario Outline: Must Alert
+      Given Mode 2 is armed
+      And Mode 2 is not inhibited
+      And the flaps are not in landing configuration
+      When the height above terrain is between 30 and <height> feet
+      And the terrain closure rate is at least <closure rate> feet per minute
+      Then a Mode 2 caution alert is emitted within 2 seconds
+
+      Examples:
+        | closure rate | height |
+        | 2100         | 100    |
+        | 2500         | 590    |
+        | 3000         | 1200   |
+        | 4500         | 1580   |
+
+    Scenario Outline: Must Alert with Airspeed Extension
+      Given Mode 2 is armed
+      And Mode 2 is not inhibited
+      And the flaps are not in landing configuration
+      And the airspeed is at least 310 knots
+      When the height above terrain is between 30 and <height> feet
+      And the terrain closure rate is at least <closure rate> feet per minute
+      Then a Mode 2 caution alert is emitted within 2 seconds
+
+      Examples:
+        | closure rate | height |
+        | 5000         | 1700   |
+        | 7000         | 2190   |
+        | 8000         | 2440   |
+
+    Scenario: Must Not Alert when not Armed
+      Given Mode 2 is not armed
+      Then a Mode 2 caution alert is not emitted at all
+
+    Scenario: Must Not Alert when Inhibited
+      Given Mode 2 is inhibited
+      Then a Mode 2 caution alert is not emitted at all
+
+    Scenario Outline: Must Not Alert
+      Given the flaps are not in landing configuration
+      When the height above terrain is not between 30 and <height> feet
+      And the terrain closure rate is at most <closure rate> feet per minute
+      Then a Mode 2 caution alert is not emitted at all
+
+      Examples:
+        | closure rate | height |
+        | 2000         | 40     |
+        | 2500         | 610    |
+        | 3000         | 1230   |
+        | 5000         | 1720   |
+        | 12000        | 2460   |
+
+    Scenario Outline: Must Not Alert without Airspeed Extension
+      Given the flaps are not in landing configuration
+      And the airspeed is at most 220 knots
+      When the height above terrain is not between 30 and 1660 feet
+      And the terrain closure rate is at most <closure rate> feet per minute
+      Then a Mode 2 caution alert is not emitted at all
+
+      Examples:
+        | closure rate |
+        | 5000         |
+        | 12000        |
+
+  #Rule: Mode 2B Caution Envelope
+
+    Scenario Outline: Must Alert
+      Given Mode 2 is armed
+      And Mode 2 is not inhibited
+      And the flaps are in landing configuration
+      When the height above terrain is between 30 and <height> feet
+      And the terrain closure rate is at least <closure rate> feet per minute
+      Then a Mode 2 caution alert is emitted within 2 seconds
+
+      Examples:
+        | closure rate | height |
+        | 2300         | 70     |
+        | 2600         | 370    |
+        | 3000         | 780    |
+        | 5000         | 780    |
+
+    Scenario Outline: Must Not Alert
+      Given the flaps are in landing configuration
+      When the height above terrain is not between 30 and <height> feet
+      And the terrain closure rate is at most <closure rate> feet per minute
+      Then a Mode 2 caution alert is not emitted at all
+
+      Examples:
+        | closure rate | height |
+        | 2200         | 40     |
+        | 2600         | 390    |
+        | 12000        | 800    |
+
+# vim: set ts=2 sw=2 expandtab: retab: expandtab #
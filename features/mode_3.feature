@@ -0,0 +1,55 @@
+Feature: Mode 3: Negative Climb Rate or Altitude Loss after Take-off or Go Around
+  The Mode 3 alert is intended to generate caution alerts when the aircraft
+  loses altitude during take-off or go around. Mode 3 arms automatically when
+  a take-off or go around is detected and disarms once the aircraft climbs
+  above a configurable height above terrain, or descends to land after a low
+  circuit. The altitude loss is measured from the highest altitude reached
+  since the take-off or go around.
+
+  Scenario: Mode Arming after Take-off
+    Given Mode 3 is not armed
+    When the aircraft takes off
+    Then Mode 3 shall be armed
+
+  Scenario: Mode Disarming
+    Given Mode 3 is not armed
+    When the aircraft takes off
+    And the aircraft climbs from 100 to 1600 feet above terrain
+    Then Mode 3 shall not be armed
+
+  #Rule: Caution Envelope
+
+    Scenario Outline: Must Alert
+      Given Mode 3 is armed
+      And Mode 3 is not inhibited
+      And the landing gear is not down
+      When the rate of descent is at most 300 feet per minute
+      And the altitude loss is at least <altitude loss> feet
+      And the height above terrain is between 30 and <height> feet
+      Then a Mode 3 caution alert is emitted within 2 seconds
+
+      Examples:
+        | altitude loss | height |
+        | 20            | 130    |
+        | 50            | 470    |
+        | 100           | 1020   |
+        | 143           | 1490   |
+
+    Scenario: Must Not Alert when Inhibited
+      Given Mode 3 is inhibited
+      Then a Mode 3 caution alert is not emitted at all
+
+    Scenario Outline: Must Not Alert
+      Given Mode 3 is armed
+      When the altitude loss is at most <altitude loss> feet
+      But the height above terrain is not between 30 and <height> feet
+      Then a Mode 3 caution alert is not emitted at all
+
+      Examples:
+        | altitude loss | height |
+        | 10            | 40     |
+        | 50            | 480    |
+        | 100           | 1030   |
+        | 500           | 1510   |
+
+# vim: set ts=2 sw=2 expandtab: retab: expandtab #
@@ -0,0 +1,101 @@
+Feature: Mode 4: Flight Near Terrain when Not in Landing Configuration
+  The Mode 4 alert is intended to generate caution alerts when the aircraft is
+  close to the terrain while not in landing configuration. Mode 4A is active
+  while the landing gear is not down, Mode 4B is active while the landing gear
+  is down but the flaps are not in landing configuration. At low airspeeds
+  they emit a gear or a flaps caution respectively, at higher airspeeds they
+  emit a terrain caution with an upper boundary rising with the airspeed.
+  Mode 4C is active during take-off and go around and emits a terrain caution
+  when the height above terrain drops below a minimum terrain clearance.
+
+  Scenario: Mode Arming/Disarming
+    Given the plane is flying
+    Then Mode 4 shall be armed
+
+  #Rule: Mode 4A Caution Envelope
+
+    Scenario Outline: Must Alert
+      Given Mode 4 is armed
+      And Mode 4 is not inhibited
+      And the landing gear is not down
+      And the airspeed is at <most or least> <airspeed> knots
+      When the rate of descent is at least 500 feet per minute
+      And the height above terrain is between 30 and <height> feet
+      Then a Mode 4 caution alert is emitted within 2 seconds
+
+      Examples:
+        | most or least | airspeed | height |
+        | most          | 180      | 490    |
+        | least         | 220      | 740    |
+        | least         | 250      | 990    |
+
+    Scenario: Must Not Alert when not Armed
+      Given Mode 4 is not armed
+      Then a Mode 4 caution alert is not emitted at all
+
+    Scenario: Must Not Alert when Inhibited
+      Given Mode 4 is inhibited
+      Then a Mode 4 caution alert is not emitted at all
+
+    Scenario: Must Not Alert without Landing Gear and Flap Position
+      Given Mode 4 is armed
+      And Mode 4 is not inhibited
+      And the landing gear position is not available
+      And the flap position is not available
+      When the rate of descent is at least 500 feet per minute
+      And the height above terrain is between 30 and 490 feet
+      Then a Mode 4 caution alert is not emitted at all
+
+    Scenario Outline: Must Not Alert
+      Given the landing gear is not down
+      And the airspeed is at <most or least> <airspeed> knots
+      When the rate of descent is at least 500 feet per minute
+      But the height above terrain is not between 30 and <height> feet
+      Then a Mode 4 caution alert is not emitted at all
+
+      Examples:
+        | most or least | airspeed | height |
+        | most          | 180      | 500    |
+        | most          | 220      | 750    |
+        | least         | 250      | 1000   |
+
+  #Rule: Mode 4B Caution Envelope
+
+    Scenario Outline: Must Alert
+      Given Mode 4 is armed
+      And Mode 4 is not inhibited
+      And the landing gear is down
+      And the flaps are not in landing configuration
+      And the airspeed is at <most or least> <airspeed> knots
+      When the rate of descent is at least 500 feet per minute
+      And the height above terrain is between 30 and <height> feet
+      Then a Mode 4 caution alert is emitted within 2 seconds
+
+      Examples:
+        | most or least | airspeed | height |
+        | most          | 150      | 240    |
+        | least         | 250      | 990    |
+
+    Scenario Outline: Must Not Alert
+      Given the landing gear is down
+      And the flaps are not in landing configuration
+      And the airspeed is at <most or least> <airspeed> knots
+      When the rate of descent is at least 500 feet per minute
+      But the height above terrain is not between 30 and <height> feet
+      Then a Mode 4 caution alert is not emitted at all
+
+      Examples:
+        | most or least | airspeed | height |
+        | most          | 150      | 250    |
+        | most          | 200      | 370    |
+        | least         | 250      | 1000   |
+
+  #Rule: Landing Configuration
+
+    Scenario: Must Not Alert in Landing Configuration
+      Given the landing gear is down
+      And the flaps are in landing configuration
+      When the rate of descent is at least 500 feet per minute
+      Then a Mode 4 caution alert is not emitted at all
+
+# vim: set ts=2 sw=2 expandtab: retab: expandtab #
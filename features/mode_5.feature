@@ -0,0 +1,67 @@
+Feature: Mode 5: Excessive Downward Deviation from an ILS Glideslope or LPV/GLS Glidepath
+  The Mode 5 alert is intended to generate caution alerts when the aircraft
+  descends too far below the glideslope or glidepath during an approach. Mode 5
+  is active while a valid glideslope signal is received and the landing gear is
+  down. A soft caution is emitted for moderate deviations, a hard caution for
+  large deviations close to the terrain. The crew may cancel the glideslope
+  alerting until the next approach.
+
+  Scenario: Mode Arming/Disarming
+    Given the plane is flying
+    Then Mode 5 shall be armed
+
+  #Rule: Soft Caution Envelope
+
+    Scenario Outline: Must Alert
+      Given Mode 5 is armed
+      And Mode 5 is not inhibited
+      And the landing gear is down
+      And the glideslope is valid
+      When the deviation below the glideslope is at least <deviation> dots
+      And the height above terrain is between <lower height> and 1000 feet
+      Then a Mode 5 caution alert is emitted within 2 seconds
+
+      Examples:
+        | deviation | lower height |
+        | 1.3       | 150          |
+        | 2.2       | 90           |
+        | 2.98      | 30           |
+
+    Scenario: Must Not Alert when not Armed
+      Given Mode 5 is not armed
+      Then a Mode 5 caution alert is not emitted at all
+
+    Scenario: Must Not Alert when Inhibited
+      Given Mode 5 is inhibited
+      Then a Mode 5 caution alert is not emitted at all
+
+    Scenario: Must Not Alert when Glideslope is Cancelled
+      Given the glideslope alert is cancelled
+      And the landing gear is down
+      And the glideslope is valid
+      When the deviation below the glideslope is at least 1.3 dots
+      And the height above terrain is between 150 and 1000 feet
+      Then a Mode 5 caution alert is not emitted at all
+
+    Scenario: Must Not Alert when Glideslope is not Valid
+      Given the glideslope is not valid
+      Then a Mode 5 caution alert is not emitted at all
+
+    Scenario: Must Not Alert when Landing Gear is not Down
+      Given the landing gear is not down
+      Then a Mode 5 caution alert is not emitted at all
+
+    Scenario Outline: Must Not Alert
+      Given the landing gear is down
+      And the glideslope is valid
+      When the deviation below the glideslope is at most <deviation> dots
+      And the height above terrain is between <lower height> and <upper height> feet
+      Then a Mode 5 caution alert is not emitted at all
+
+      Examples:
+        | deviation | lower height | upper height |
+        | 1.29      | 150          | 1000         |
+        | 2.1       | 30           | 90           |
+        | 2.9       | 30           | 35           |
+
+# vim: set ts=2 sw=2 expandtab: retab: expandtab #
@@ -0,0 +1,23 @@
+Feature: Mode 6: Altitude Callouts
+  Altitude callouts are annunciated once per approach, whenever the aircraft descends through one
+  of the configured heights above terrain.
+
+  Scenario: Mode Arming/Disarming
+    Given the plane is flying
+    Then Mode 6 shall be armed
+
+  Scenario: Callout when descending through a callout height
+    Given Mode 6 is armed
+    And Mode 6 is not inhibited
+    And the height above terrain is greater or equal to 600 foot
+    When the height above terrain is lower than 500 foot
+    Then a Mode 6 annunciation alert is emitted within 1.3 seconds
+
+  Scenario: No callout when not armed
+    Given Mode 6 is not armed
+    And Mode 6 is not inhibited
+    And the height above terrain is greater or equal to 600 foot
+    When the height above terrain is lower than 500 foot
+    Then a Mode 6 annunciation alert is not emitted at all
+
+# vim: set ts=2 sw=2 expandtab: retab: expandtab #
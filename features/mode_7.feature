@@ -0,0 +1,45 @@
+Feature: Mode 7: Reactive Windshear
+  Windshear is detected from the rate at which the headwind changes and from the vertical speed of
+  the air mass. A shear which decreases the performance of the aircraft, like a loss of headwind,
+  triggers a warning. A shear which increases the performance triggers a caution.
+
+  Scenario: Mode Arming/Disarming
+    Given the plane is flying
+    Then Mode 7 shall be armed
+
+  Scenario: Warning for decreasing performance shear
+    Given Mode 7 is armed
+    And Mode 7 is not inhibited
+    When the height above terrain is between 100 and 1000 feet
+    And the headwind decreases by 3 knots per second
+    Then a Mode 7 warning alert is emitted within 2 seconds
+
+  Scenario: Caution for increasing performance shear
+    Given Mode 7 is armed
+    And Mode 7 is not inhibited
+    When the height above terrain is between 100 and 1000 feet
+    And the headwind increases by 3 knots per second
+    Then a Mode 7 caution alert is emitted within 2 seconds
+
+  Scenario: No alert for a weak shear
+    Given Mode 7 is armed
+    And Mode 7 is not inhibited
+    When the height above terrain is between 100 and 1000 feet
+    And the headwind decreases by 1 knots per second
+    Then a Mode 7 caution alert is not emitted at all
+
+  Scenario: No alert above the maximum height
+    Given Mode 7 is armed
+    And Mode 7 is not inhibited
+    When the height above terrain is between 1600 and 5000 feet
+    And the headwind decreases by 3 knots per second
+    Then a Mode 7 caution alert is not emitted at all
+
+  Scenario: No alert when not armed
+    Given Mode 7 is not armed
+    And Mode 7 is not inhibited
+    When the height above terrain is between 100 and 1000 feet
+    And the headwind decreases by 3 knots per second
+    Then a Mode 7 caution alert is not emitted at all
+
+# vim: set ts=2 sw=2 expandtab: retab: expandtab #
@@ -0,0 +1,63 @@
+Feature: Premature Descent Alerting
+  The PDA alert is intended to generate caution alerts when the aircraft
+  descends well below a normal approach path. The height above terrain is
+  compared against the distance to the nearest runway, closer than one
+  nautical mile to the runway no alert is given. PDA is only active while the
+  aircraft is descending.
+
+  Scenario: Mode Arming/Disarming
+    Given the plane is flying
+    Then PDA shall be armed
+
+  #Rule: Caution Envelope
+
+    Scenario Outline: Must Alert
+      Given PDA is armed
+      And PDA is not inhibited
+      When the rate of descent is at least 500 feet per minute
+      And the nearest runway is between <min distance> and <max distance> nautical miles away
+      And the height above terrain is between 30 and <height> feet
+      Then a PDA caution alert is emitted within 2 seconds
+
+      Examples:
+        | min distance | max distance | height |
+        | 2            | 5            | 140    |
+        | 5            | 10           | 390    |
+        | 10           | 15           | 690    |
+
+    Scenario: Must Not Alert when Terrain is Inhibited
+      Given PDA is armed
+      And PDA is not inhibited
+      And the terrain inhibit switch is active
+      When the rate of descent is at least 500 feet per minute
+      And the nearest runway is between 2 and 5 nautical miles away
+      And the height above terrain is between 30 and 140 feet
+      Then a PDA caution alert is not emitted at all
+
+    Scenario: Must Not Alert when not Armed
+      Given PDA is not armed
+      Then a PDA caution alert is not emitted at all
+
+    Scenario: Must Not Alert when Inhibited
+      Given PDA is inhibited
+      Then a PDA caution alert is not emitted at all
+
+    Scenario Outline: Must Not Alert
+      When the rate of descent is at least 500 feet per minute
+      And the nearest runway is between <min distance> and <max distance> nautical miles away
+      But the height above terrain is not between 30 and <height> feet
+      Then a PDA caution alert is not emitted at all
+
+      Examples:
+        | min distance | max distance | height |
+        | 2            | 5            | 410    |
+        | 5            | 10           | 710    |
+        | 16           | 100          | 40     |
+
+    Scenario: Must Not Alert when Climbing
+      When the rate of descent is at most 0 feet per minute
+      And the nearest runway is between 2 and 15 nautical miles away
+      And the height above terrain is between 30 and 100 feet
+      Then a PDA caution alert is not emitted at all
+
+# vim: set ts=2 sw=2 expandtab: retab: expandtab #
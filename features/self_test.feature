@@ -12,6 +12,7 @@ Feature: Self-Test (MOPS_001)
   # can be done using interaction with the crew
   @MOPS_005
   Scenario Outline: Initiated Self-Test
+    Given the signal test is enabled
     When the self-test is initiated
     Then the TAWS must verify system operation and integrity of <capability>
 
@@ -29,10 +30,17 @@ Feature: Self-Test (MOPS_001)
     When the initiated self-test detects a failure
     Then the failure must be reported
 
-  #Scenario: Automatic Arming (MOPS_007)
+  # Automatic Arming (MOPS_007) is covered in flight_phase.feature
 
-  Scenario: Input Data Smoothing
-    When the rate of input data reduces or stagnates
-    Then the delays to alert onset do not 
+  Scenario Outline: Input Data Smoothing
+    Given the signal test is enabled
+    When the rate of input data <change>
+    Then the aircraft state is rejected as <reason>
+    And Mode 1 is skipped
+
+    Examples:
+      | change    | reason                   |
+      | reduces   | input data stale         |
+      | stagnates | timestamp not increasing |
 
 # vim: set ts=2 sw=2 expandtab: retab: expandtab #
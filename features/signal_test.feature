@@ -0,0 +1,65 @@
+Feature: Signal Test
+  Before the alert systems process an aircraft state, it is checked for plausibility. Alert systems
+  relying on an implausible signal skip the aircraft state, the faults and the skipped alert
+  systems are reported instead.
+
+  Scenario: Plausible aircraft states reach the alert systems
+    Given the signal test is enabled
+    And Mode 3 is not armed
+    When the aircraft takes off
+    Then Mode 3 shall be armed
+
+  Scenario Outline: Implausible aircraft states are rejected
+    Given the signal test is enabled
+    When an aircraft state <change> is processed
+    Then the aircraft state is rejected as <reason>
+
+    Examples:
+      | change                            | reason                        |
+      | from the past                     | timestamp not increasing      |
+      | below the terrain                 | negative height above terrain |
+      | 2000 feet higher one second later | implausible altitude change   |
+      | 100 knots faster one second later | implausible airspeed change   |
+
+  Scenario: Processing recovers after a reset of the timestamps
+    Given the signal test is enabled
+    And Mode 3 is not armed
+    When an aircraft state from the past is processed
+    And the aircraft takes off
+    Then Mode 3 shall be armed
+
+  Scenario Outline: Only alert systems relying on an implausible signal are skipped
+    Given the signal test is enabled
+    When an aircraft state <change> is processed
+    Then <skipped> is skipped
+    And <processed> is not skipped
+
+    Examples:
+      | change                            | skipped | processed |
+      | below the terrain                 | Mode 1  | FLTA      |
+      | 2000 feet higher one second later | Mode 3  | Mode 1    |
+      | 100 knots faster one second later | Mode 2  | FLTA      |
+
+  Scenario: Faults in basic modes are annunciated as TAWS INOP
+    Given the signal test is enabled
+    When an aircraft state 100 knots faster one second later is processed
+    Then TAWS INOP is annunciated
+
+  Scenario: Plausible aircraft states are annunciated as operative
+    Given the signal test is enabled
+    When an aircraft state one second later is processed
+    Then TAWS INOP is not annunciated
+    And Mode 1 is not skipped
+
+  Scenario: Missing terrain data is not annunciated as TERR FAIL
+    When an aircraft state one second later is processed
+    Then TERR FAIL is not annunciated
+    And FLTA shall not be armed
+
+  Scenario: Unavailable terrain data is annunciated as TERR FAIL
+    Given the terrain data is unavailable
+    When an aircraft state one second later is processed
+    Then TERR FAIL is annunciated
+    And TAWS INOP is not annunciated
+
+# vim: set ts=2 sw=2 expandtab: retab: expandtab #
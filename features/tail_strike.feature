@@ -0,0 +1,43 @@
+Feature: Tail Strike: Pitch Attitude close to a Tail Strike
+  Close to the ground a caution is given when the pitch attitude comes within two degrees of the
+  aircraft specific pitch limit at which the tail touches the ground. This covers the take-off
+  rotation as well as the flare.
+
+  Scenario: Not armed without a pitch limit
+    Given the plane is flying
+    Then Tail Strike shall not be armed
+
+  Scenario: Armed with a pitch limit
+    Given the tail strike pitch limit is 11 degrees
+    Then Tail Strike shall be armed
+
+  Scenario: Caution when approaching the pitch limit
+    Given the tail strike pitch limit is 11 degrees
+    And Tail Strike is not inhibited
+    When the height above terrain is between 0 and 29 feet
+    And the pitch angle is at least 9 degrees
+    Then a Tail Strike caution alert is emitted within 1 seconds
+
+  Scenario: No caution well below the pitch limit
+    Given the tail strike pitch limit is 11 degrees
+    And Tail Strike is not inhibited
+    When the height above terrain is between 0 and 29 feet
+    And the pitch angle is at most 8 degrees
+    Then a Tail Strike caution alert is not emitted at all
+
+  Scenario: No caution away from the ground
+    Given the tail strike pitch limit is 11 degrees
+    And Tail Strike is not inhibited
+    When the height above terrain is between 30 and 1000 feet
+    And the pitch angle is at least 9 degrees
+    Then a Tail Strike caution alert is not emitted at all
+
+  Scenario: No caution when not armed
+    Given the tail strike pitch limit is 11 degrees
+    And Tail Strike is not armed
+    And Tail Strike is not inhibited
+    When the height above terrain is between 0 and 29 feet
+    And the pitch angle is at least 9 degrees
+    Then a Tail Strike caution alert is not emitted at all
+
+# vim: set ts=2 sw=2 expandtab: retab: expandtab #
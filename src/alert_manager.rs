@@ -0,0 +1,472 @@
+//! Keeps the alerts stable over successive `AircraftState`s, so that an alert does not flicker on
+//! and off whenever a noisy input crosses an envelope boundary
+
+use crate::alerts::ALERT_STATE_SIZE;
+use crate::prelude::*;
+
+/// Onset and clearing of an alert, as time stamps of the `AircraftState`s in which they happened
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AlertTiming {
+    /// Time stamp at which the alert became present
+    pub onset: Time,
+
+    /// Time stamp at which the alert cleared, `None` while it is present
+    pub cleared: Option<Time>,
+}
+
+/// Change of an alert from one `AircraftState` to the next
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AlertEvent {
+    /// The alert became present, or changed to another sub-type on the same level, e.g. the
+    /// next altitude callout
+    Onset { alert: Alert, level: AlertLevel },
+
+    /// The alert changed to a higher level, e.g. from a caution to a warning
+    Escalated { alert: Alert, level: AlertLevel },
+
+    /// The alert changed to a lower level, e.g. from a warning to a caution
+    Downgraded { alert: Alert, level: AlertLevel },
+
+    /// The alert is no longer present, as its condition is gone
+    Cleared { alert: Alert },
+
+    /// The alert is no longer present, as its alert system was inhibited
+    Inhibited { alert: Alert },
+
+    /// The alert is no longer present, as its alert system failed
+    Failed { alert: Alert },
+}
+
+impl AlertEvent {
+    /// The alert which changed
+    pub fn alert(&self) -> Alert {
+        match self {
+            AlertEvent::Onset { alert, .. }
+            | AlertEvent::Escalated { alert, .. }
+            | AlertEvent::Downgraded { alert, .. }
+            | AlertEvent::Cleared { alert }
+            | AlertEvent::Inhibited { alert }
+            | AlertEvent::Failed { alert } => *alert,
+        }
+    }
+}
+
+/// The `AlertEvent`s of one `AircraftState`, there is at most one for each `Alert`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AlertEvents {
+    events: [Option<AlertEvent>; ALERT_STATE_SIZE],
+}
+
+impl AlertEvents {
+    /// Number of events
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// `true` if no alert changed
+    pub fn is_empty(&self) -> bool {
+        self.events.iter().all(Option::is_none)
+    }
+
+    /// Get an iterator to the events
+    pub fn iter(&self) -> impl Iterator<Item = AlertEvent> + '_ {
+        self.events.iter().flatten().copied()
+    }
+
+    fn push(&mut self, event: AlertEvent) {
+        if let Some(option) = self.events.iter_mut().find(|e| e.is_none()) {
+            *option = Some(event);
+        }
+    }
+}
+
+/// An alert, as it is presented to the crew
+#[derive(Clone, Copy, Debug)]
+struct ManagedAlert {
+    alert: Alert,
+    level: AlertLevel,
+    sub_type: Option<AlertSubType>,
+    timing: AlertTiming,
+
+    /// Time stamp at which the alert system last emitted the alert at `level` or a higher level
+    last_emitted: Time,
+}
+
+impl ManagedAlert {
+    /// Whether the alert is still held at its level, although its condition is gone
+    ///
+    /// A time stamp older than the last emission means that the time was reset, which releases
+    /// the alert.
+    fn is_held(&self, timestamp: Time, config: &TawsConfig) -> bool {
+        let since_onset = timestamp - self.timing.onset;
+        let since_emitted = timestamp - self.last_emitted;
+
+        since_emitted >= Time::new::<second>(0.0)
+            && (since_onset < config.alert_min_duration
+                || since_emitted < (config.alert_clear_delay)(self.alert))
+    }
+}
+
+/// Latches alerts for a minimum time and applies a hysteresis when they clear or downgrade
+#[derive(Debug, Default)]
+pub(crate) struct AlertManager {
+    /// The alerts which are present, or the last onset and clearing of those which are not
+    alerts: [Option<ManagedAlert>; ALERT_STATE_SIZE],
+
+    /// The changes of the alerts during the last update
+    events: AlertEvents,
+}
+
+impl AlertManager {
+    /// Merges the alerts emitted by the alert systems for one `AircraftState` with those which are
+    /// held, returns the alerts to present to the crew
+    ///
+    /// Alerts of inhibited or failed alert systems clear immediately. A higher level replaces a
+    /// lower one immediately, while a lower level replaces a higher one only once the higher one
+    /// is no longer held.
+    pub fn update(
+        &mut self,
+        timestamp: Time,
+        emitted: &AlertState,
+        inhibited: impl Fn(Alert) -> bool,
+        failed: impl Fn(Alert) -> bool,
+        config: &TawsConfig,
+    ) -> AlertState {
+        let events = &mut self.events;
+        *events = AlertEvents::default();
+
+        for managed in self.alerts.iter_mut().flatten() {
+            let alert = managed.alert;
+            let present = managed.timing.cleared.is_none();
+            let last_level = managed.level;
+
+            let dropped = if inhibited(alert) {
+                Some(AlertEvent::Inhibited { alert })
+            } else if failed(alert) {
+                Some(AlertEvent::Failed { alert })
+            } else {
+                None
+            };
+            if let Some(event) = dropped {
+                if present {
+                    managed.timing.cleared = Some(timestamp);
+                    events.push(event);
+                }
+                continue;
+            }
+
+            match emitted.level(alert) {
+                Some(level)
+                    if present
+                        && level == last_level
+                        && emitted.sub_type(alert) != managed.sub_type =>
+                {
+                    *managed = Self::onset(alert, level, emitted, timestamp);
+                    events.push(AlertEvent::Onset { alert, level });
+                }
+                Some(level)
+                    if present && (level <= last_level || !managed.is_held(timestamp, config)) =>
+                {
+                    managed.level = level;
+                    managed.sub_type = emitted.sub_type(alert);
+                    managed.last_emitted = timestamp;
+
+                    if level < last_level {
+                        events.push(AlertEvent::Escalated { alert, level });
+                    } else if level > last_level {
+                        events.push(AlertEvent::Downgraded { alert, level });
+                    }
+                }
+                Some(_) if present => {}
+                Some(level) => {
+                    *managed = Self::onset(alert, level, emitted, timestamp);
+                    events.push(AlertEvent::Onset { alert, level });
+                }
+                None if present && !managed.is_held(timestamp, config) => {
+                    managed.timing.cleared = Some(timestamp);
+                    events.push(AlertEvent::Cleared { alert });
+                }
+                None => {}
+            }
+        }
+
+        for (alert, level) in emitted.iter() {
+            let known = self.alerts.iter().flatten().any(|e| e.alert == alert);
+            if known || inhibited(alert) || failed(alert) {
+                continue;
+            }
+            if let Some(option) = self.alerts.iter_mut().find(|e| e.is_none()) {
+                *option = Some(Self::onset(alert, level, emitted, timestamp));
+                self.events.push(AlertEvent::Onset { alert, level });
+            }
+        }
+
+        let mut alert_state = AlertState::new(config.alert_priorities);
+        for managed in self.present() {
+            alert_state.insert(managed.alert, managed.level, managed.sub_type);
+        }
+        alert_state
+    }
+
+    /// The changes of the alerts during the last update
+    pub fn events(&self) -> AlertEvents {
+        self.events
+    }
+
+    /// Onset and clearing of all alerts which occurred so far
+    pub fn timings(&self) -> impl Iterator<Item = (Alert, AlertTiming)> + '_ {
+        self.alerts
+            .iter()
+            .flatten()
+            .map(|managed| (managed.alert, managed.timing))
+    }
+
+    fn present(&self) -> impl Iterator<Item = &ManagedAlert> {
+        self.alerts
+            .iter()
+            .flatten()
+            .filter(|managed| managed.timing.cleared.is_none())
+    }
+
+    fn onset(
+        alert: Alert,
+        level: AlertLevel,
+        emitted: &AlertState,
+        timestamp: Time,
+    ) -> ManagedAlert {
+        ManagedAlert {
+            alert,
+            level,
+            sub_type: emitted.sub_type(alert),
+            timing: AlertTiming {
+                onset: timestamp,
+                cleared: None,
+            },
+            last_emitted: timestamp,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn emitted(alerts: &[(Alert, AlertLevel)]) -> AlertState {
+        let mut alert_state = AlertState::default();
+        for (alert, level) in alerts {
+            alert_state.insert(*alert, *level, None);
+        }
+        alert_state
+    }
+
+    fn update(manager: &mut AlertManager, time: f64, alerts: &[(Alert, AlertLevel)]) -> AlertState {
+        manager.update(
+            Time::new::<second>(time),
+            &emitted(alerts),
+            |_| false,
+            |_| false,
+            &TawsConfig::default(),
+        )
+    }
+
+    const CAUTION: (Alert, AlertLevel) = (Alert::Mode1, AlertLevel::Caution);
+    const WARNING: (Alert, AlertLevel) = (Alert::Mode1, AlertLevel::Warning);
+
+    #[test]
+    fn latched_for_minimum_duration() {
+        let mut manager = AlertManager::default();
+
+        update(&mut manager, 0.0, &[CAUTION]);
+        assert_eq!(update(&mut manager, 1.5, &[]), emitted(&[CAUTION]));
+        assert_eq!(update(&mut manager, 2.0, &[]), emitted(&[]));
+
+        let timings: std::vec::Vec<_> = manager.timings().collect();
+        assert_eq!(
+            timings,
+            [(
+                Alert::Mode1,
+                AlertTiming {
+                    onset: Time::new::<second>(0.0),
+                    cleared: Some(Time::new::<second>(2.0)),
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn hysteresis_on_clearing() {
+        let mut manager = AlertManager::default();
+
+        for time in [0.0, 1.0, 2.0, 3.0] {
+            update(&mut manager, time, &[CAUTION]);
+        }
+        assert_eq!(update(&mut manager, 3.5, &[]), emitted(&[CAUTION]));
+        assert_eq!(update(&mut manager, 4.0, &[]), emitted(&[]));
+    }
+
+    #[test]
+    fn escalates_immediately_downgrades_late() {
+        let mut manager = AlertManager::default();
+
+        update(&mut manager, 0.0, &[CAUTION]);
+        assert_eq!(update(&mut manager, 0.1, &[WARNING]), emitted(&[WARNING]));
+        assert_eq!(update(&mut manager, 1.0, &[CAUTION]), emitted(&[WARNING]));
+        assert_eq!(update(&mut manager, 2.0, &[CAUTION]), emitted(&[CAUTION]));
+
+        // the onset is the one of the caution
+        let (_, timing) = manager.timings().next().unwrap();
+        assert_eq!(timing.onset, Time::new::<second>(0.0));
+    }
+
+    #[test]
+    fn new_onset_after_clearing() {
+        let mut manager = AlertManager::default();
+
+        update(&mut manager, 0.0, &[CAUTION]);
+        update(&mut manager, 5.0, &[]);
+        assert_eq!(update(&mut manager, 6.0, &[CAUTION]), emitted(&[CAUTION]));
+
+        let (_, timing) = manager.timings().next().unwrap();
+        assert_eq!(timing.onset, Time::new::<second>(6.0));
+        assert_eq!(timing.cleared, None);
+    }
+
+    #[test]
+    fn inhibited_alerts_clear_immediately() {
+        let mut manager = AlertManager::default();
+        let config = TawsConfig::default();
+
+        update(&mut manager, 0.0, &[CAUTION]);
+        let alert_state = manager.update(
+            Time::new::<second>(0.1),
+            &emitted(&[]),
+            |_| true,
+            |_| false,
+            &config,
+        );
+        assert_eq!(alert_state, emitted(&[]));
+    }
+
+    #[test]
+    fn failed_alerts_clear_immediately() {
+        let mut manager = AlertManager::default();
+        let config = TawsConfig::default();
+
+        update(&mut manager, 0.0, &[CAUTION]);
+        let alert_state = manager.update(
+            Time::new::<second>(0.1),
+            &emitted(&[]),
+            |_| false,
+            |_| true,
+            &config,
+        );
+        assert_eq!(alert_state, emitted(&[]));
+        assert!(manager.events().iter().eq([AlertEvent::Failed {
+            alert: Alert::Mode1
+        }]));
+    }
+
+    #[test]
+    fn events_of_an_alert() {
+        let mut manager = AlertManager::default();
+        let mut events = |time, alerts: &[(Alert, AlertLevel)]| {
+            update(&mut manager, time, alerts);
+            manager.events().iter().collect::<std::vec::Vec<_>>()
+        };
+        let alert = Alert::Mode1;
+
+        assert_eq!(
+            events(0.0, &[CAUTION]),
+            [AlertEvent::Onset {
+                alert,
+                level: AlertLevel::Caution
+            }]
+        );
+        assert_eq!(events(0.5, &[CAUTION]), []);
+        assert_eq!(
+            events(1.0, &[WARNING]),
+            [AlertEvent::Escalated {
+                alert,
+                level: AlertLevel::Warning
+            }]
+        );
+        assert_eq!(
+            events(3.0, &[CAUTION]),
+            [AlertEvent::Downgraded {
+                alert,
+                level: AlertLevel::Caution
+            }]
+        );
+        assert_eq!(events(5.0, &[]), [AlertEvent::Cleared { alert }]);
+        assert_eq!(events(6.0, &[]), []);
+    }
+
+    #[test]
+    fn successive_callouts() {
+        let mut manager = AlertManager::default();
+        let config = TawsConfig::default();
+        let mut callout = |time, callout| {
+            let mut alert_state = AlertState::default();
+            alert_state.insert(
+                Alert::Mode6,
+                AlertLevel::Annunciation,
+                Some(AlertSubType::Callout(callout)),
+            );
+            let presented = manager.update(
+                Time::new::<second>(time),
+                &alert_state,
+                |_| false,
+                |_| false,
+                &config,
+            );
+            (presented, manager.events())
+        };
+        let onset = AlertEvent::Onset {
+            alert: Alert::Mode6,
+            level: AlertLevel::Annunciation,
+        };
+
+        let (_, events) = callout(0.0, Callout::OneHundred);
+        assert!(events.iter().eq([onset]));
+
+        // the next callout while the previous one is still held
+        let (presented, events) = callout(0.5, Callout::Fifty);
+        assert!(events.iter().eq([onset]));
+        assert_eq!(
+            presented.sub_type(Alert::Mode6),
+            Some(AlertSubType::Callout(Callout::Fifty))
+        );
+
+        let (_, timing) = manager.timings().next().unwrap();
+        assert_eq!(timing.onset, Time::new::<second>(0.5));
+    }
+
+    #[test]
+    fn inhibited_event() {
+        let mut manager = AlertManager::default();
+        let config = TawsConfig::default();
+
+        update(&mut manager, 0.0, &[CAUTION]);
+        manager.update(
+            Time::new::<second>(0.1),
+            &emitted(&[]),
+            |_| true,
+            |_| false,
+            &config,
+        );
+        assert!(manager.events().iter().eq([AlertEvent::Inhibited {
+            alert: Alert::Mode1
+        }]));
+    }
+
+    #[test]
+    fn time_reset_releases_alerts() {
+        let mut manager = AlertManager::default();
+
+        update(&mut manager, 100.0, &[CAUTION]);
+        assert_eq!(update(&mut manager, 0.0, &[]), emitted(&[]));
+    }
+}
@@ -0,0 +1,124 @@
+use crate::prelude::*;
+
+use super::*;
+
+#[derive(Debug)]
+pub struct BankAngle {
+    armed: bool,
+    inhibited: bool,
+
+    /// Alert level emitted in the last frame, used for the hysteresis
+    level: Option<AlertLevel>,
+}
+
+impl<'a> AlertSystem<'a> for BankAngle {
+    fn new(_config: &TawsConfig<'a>) -> Self {
+        Self {
+            armed: true,
+            inhibited: false,
+            level: None,
+        }
+    }
+
+    arm_inhibit!();
+
+    fn process(&mut self, state: &AircraftState) -> Option<AlertLevel> {
+        let altitude = state.altitude_ground.get::<foot>();
+
+        // roll angle in degrees, regardless of the direction
+        let roll = libm::fabs(libm::remainder(state.roll.get::<degree>(), 360.0));
+
+        let warning_limit = interpolate(altitude, (30.0, 15.0), (150.0, 45.0));
+        let caution_limit = interpolate(altitude, (30.0, 10.0), (150.0, 35.0));
+
+        // an active alert only clears once the roll angle is well below its limit
+        let hysteresis = |limit: f64, level: AlertLevel| match self.level {
+            Some(active) if active <= level => limit - Self::HYSTERESIS,
+            _ => limit,
+        };
+
+        self.level = if !self.armed || altitude < Self::MIN_HEIGHT {
+            None
+        } else if roll > hysteresis(warning_limit, AlertLevel::Warning) {
+            Some(AlertLevel::Warning)
+        } else if roll > hysteresis(caution_limit, AlertLevel::Caution) {
+            Some(AlertLevel::Caution)
+        } else {
+            None
+        };
+
+        self.level
+    }
+}
+
+impl BankAngle {
+    /// Height above terrain below which no bank angle alerts are emitted
+    const MIN_HEIGHT: f64 = 5.0;
+
+    /// Degrees by which the roll angle must fall below a limit to clear the alert
+    const HYSTERESIS: f64 = 5.0;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn state(height: f64, roll: f64) -> AircraftState {
+        AircraftState {
+            altitude: Length::new::<foot>(height),
+            altitude_ground: Length::new::<foot>(height),
+            roll: Angle::new::<degree>(roll),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn limits_depend_on_height() {
+        let mut bank_angle = BankAngle::new(&Default::default());
+
+        assert_eq!(bank_angle.process(&state(500.0, 30.0)), None);
+        assert_eq!(
+            bank_angle.process(&state(30.0, 30.0)),
+            Some(AlertLevel::Warning)
+        );
+        assert_eq!(bank_angle.process(&state(2.0, 30.0)), None);
+    }
+
+    #[test]
+    fn roll_in_both_directions() {
+        let mut bank_angle = BankAngle::new(&Default::default());
+
+        assert_eq!(
+            bank_angle.process(&state(500.0, -40.0)),
+            Some(AlertLevel::Caution)
+        );
+        assert_eq!(
+            bank_angle.process(&state(500.0, 310.0)),
+            Some(AlertLevel::Warning)
+        );
+    }
+
+    #[test]
+    fn hysteresis() {
+        let mut bank_angle = BankAngle::new(&Default::default());
+
+        assert_eq!(
+            bank_angle.process(&state(500.0, 46.0)),
+            Some(AlertLevel::Warning)
+        );
+        assert_eq!(
+            bank_angle.process(&state(500.0, 42.0)),
+            Some(AlertLevel::Warning)
+        );
+        assert_eq!(
+            bank_angle.process(&state(500.0, 39.0)),
+            Some(AlertLevel::Caution)
+        );
+        assert_eq!(
+            bank_angle.process(&state(500.0, 32.0)),
+            Some(AlertLevel::Caution)
+        );
+        assert_eq!(bank_angle.process(&state(500.0, 29.0)), None);
+        assert_eq!(bank_angle.process(&state(500.0, 32.0)), None);
+    }
+}
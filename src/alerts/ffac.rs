@@ -1,34 +1,123 @@
+use uom::si::length::nautical_mile;
+
 use super::*;
 use crate::prelude::*;
 
 #[derive(Debug)]
-pub struct Ffac {
+pub struct Ffac<'a> {
     armed: bool,
     inhibited: bool,
-    last_height: Length,
+    runway_server: Option<&'a dyn RunwayServer>,
+    last_height: Option<Length>,
 }
 
-impl AlertSystem for Ffac {
-    fn new(_config: &TawsConfig) -> Self {
+impl<'a> AlertSystem<'a> for Ffac<'a> {
+    fn new(config: &TawsConfig<'a>) -> Self {
         Self {
             armed: true,
             inhibited: false,
-            last_height: Length::new::<foot>(0.0),
+            runway_server: config.runway_server,
+            last_height: None,
         }
     }
 
     arm_inhibit!();
 
+    fn flight_phase_changed(&mut self, phase: FlightPhase) {
+        match phase {
+            FlightPhase::Approach => self.armed = true,
+            FlightPhase::Takeoff => self.armed = false,
+            _ => {}
+        }
+    }
+
     fn process(&mut self, state: &AircraftState) -> Option<AlertLevel> {
         let fivehundred = Length::new::<foot>(500.0);
+        let height = self.height(state);
+        let last_height = self.last_height.replace(height)?;
 
-        if self.last_height >= fivehundred && state.altitude_ground < fivehundred {
-            return Some(AlertLevel::Annunciation);
+        if !self.armed || state.approach != ApproachType::NonPrecision {
+            return None;
         }
 
-        // TODO check against runway elevation (MOPS_292)
-        //
+        if last_height >= fivehundred && height < fivehundred {
+            return Some(AlertLevel::Annunciation);
+        }
 
         None
     }
 }
+
+impl Ffac<'_> {
+    /// Distance to the nearest runway in nautical miles beyond which its elevation is not used
+    const MAX_RUNWAY_DISTANCE: f64 = 15.0;
+
+    /// Height above the nearest runway threshold (MOPS_292), or height above terrain if no runway
+    /// is known within `MAX_RUNWAY_DISTANCE`
+    fn height(&self, state: &AircraftState) -> Length {
+        let position = Position::from(state);
+        let max_distance = Length::new::<nautical_mile>(Self::MAX_RUNWAY_DISTANCE);
+
+        match self
+            .runway_server
+            .and_then(|server| server.nearest_runway(&position).ok().flatten())
+            .filter(|runway| position.distance(&runway.location) <= max_distance)
+        {
+            Some(runway) => state.altitude - runway.location.altitude_sea,
+            None => state.altitude_ground,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::terrain_server::test::NullIsland;
+
+    /// Descends through 500 feet above sea level on a non-precision approach, while the terrain
+    /// lies 1000 feet above sea level
+    fn descend(ffac: &mut Ffac, latitude: f64) -> Option<AlertLevel> {
+        [600.0, 400.0]
+            .iter()
+            .map(|altitude| AircraftState {
+                altitude: Length::new::<foot>(*altitude),
+                altitude_ground: Length::new::<foot>(*altitude - 1000.0),
+                position_lat: Angle::new::<degree>(latitude),
+                approach: ApproachType::NonPrecision,
+                ..Default::default()
+            })
+            .map(|state| ffac.process(&state))
+            .last()
+            .flatten()
+    }
+
+    fn ffac() -> Ffac<'static> {
+        Ffac::new(&TawsConfig {
+            runway_server: Some(&NullIsland),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn callout_above_nearby_runway() {
+        assert_eq!(descend(&mut ffac(), 0.1), Some(AlertLevel::Annunciation));
+    }
+
+    #[test]
+    fn distant_runway_is_ignored() {
+        assert_eq!(descend(&mut ffac(), 1.0), None);
+    }
+
+    #[test]
+    fn no_callout_without_approach_type() {
+        let mut ffac = ffac();
+        for altitude in [600.0, 400.0] {
+            let state = AircraftState {
+                altitude: Length::new::<foot>(altitude),
+                altitude_ground: Length::new::<foot>(altitude),
+                ..Default::default()
+            };
+            assert_eq!(ffac.process(&state), None);
+        }
+    }
+}
@@ -1,22 +1,454 @@
+use uom::si::angular_velocity::degree_per_second;
+use uom::si::length::nautical_mile;
+
+use crate::prelude::*;
+
 use super::*;
 
 #[derive(Debug)]
-pub struct Flta {
+pub struct Flta<'a> {
     armed: bool,
     inhibited: bool,
+    terrain_server: Option<&'a dyn TerrainServer>,
+    terrain_failed: bool,
+    runway_server: Option<&'a dyn RunwayServer>,
+    approach: bool,
+    caution_time: Time,
+    warning_time: Time,
+    required_clearance: Length,
+    max_climbrate: Velocity,
+    max_climbrate_change: Acceleration,
+    last_heading: Option<(Time, Angle)>,
 }
 
-impl AlertSystem for Flta {
-    fn new(_config: &TawsConfig) -> Self {
+impl<'a> AlertSystem<'a> for Flta<'a> {
+    fn new(config: &TawsConfig<'a>) -> Self {
         Self {
-            armed: false,
+            // FLTA is not installed without terrain data, which is no failure
+            armed: config.terrain_server.is_some(),
             inhibited: false,
+            terrain_server: config.terrain_server,
+            terrain_failed: false,
+            runway_server: config.runway_server,
+            approach: false,
+            caution_time: config.flta_caution_time,
+            warning_time: config.flta_warning_time,
+            required_clearance: config.flta_required_clearance,
+            max_climbrate: config.max_climbrate,
+            max_climbrate_change: config.max_climbrate_change,
+            last_heading: None,
         }
     }
 
     arm_inhibit!();
 
-    fn process(&mut self, _state: &AircraftState) -> Option<AlertLevel> {
-        None
+    fn flight_phase_changed(&mut self, phase: FlightPhase) {
+        // on and close to the ground, every flight path violates the required clearance
+        self.armed = self.terrain_server.is_some()
+            && !matches!(
+                phase,
+                FlightPhase::Ground | FlightPhase::Takeoff | FlightPhase::Landing
+            );
+        self.approach = phase == FlightPhase::Approach;
+    }
+
+    fn process(&mut self, state: &AircraftState) -> Option<AlertLevel> {
+        let turn_rate = self.turn_rate(state);
+
+        if !self.armed || state.terrain_inhibit == Discrete::Active {
+            return None;
+        }
+
+        let search = self.search(self.terrain_server?, state, turn_rate);
+        self.terrain_failed = search.is_err();
+        let conflict = search.ok().flatten()?;
+
+        if conflict.time <= self.warning_time || !conflict.escapable {
+            Some(AlertLevel::Warning)
+        } else if conflict.time <= self.caution_time {
+            Some(AlertLevel::Caution)
+        } else {
+            None
+        }
+    }
+
+    fn is_failed(&self) -> bool {
+        self.terrain_failed
+    }
+}
+
+/// First violation of the required terrain clearance along the projected flight path
+struct Conflict {
+    /// Look-ahead time at which the violation occurs
+    time: Time,
+
+    /// `true` if a climb with the maximum climb rate keeps the aircraft above the terrain
+    escapable: bool,
+}
+
+impl Flta<'_> {
+    /// Time between two samples along the projected flight path
+    const STEP: f64 = 1.0;
+
+    /// Required clearance in foot on approach, reduced from the en route clearance as DO-367 does
+    /// for the approach phase
+    const APPROACH_CLEARANCE: f64 = 350.0;
+
+    /// Height in foot above the terrain below the aircraft under which the projected flight path
+    /// ends on an approach without a known runway, as the descent is expected to end there
+    const APPROACH_FLOOR: f64 = 500.0;
+
+    /// Distance to the nearest runway in nautical miles beyond which it is not considered to be
+    /// approached
+    const MAX_RUNWAY_DISTANCE: f64 = 15.0;
+
+    /// Distance to the approached runway in nautical miles below which the required clearance
+    /// shrinks in proportion to the distance
+    const CLEARANCE_DISTANCE: f64 = 5.0;
+
+    /// Distance to the approached runway in nautical miles at which the projected flight path
+    /// ends, as the aircraft is expected to land there
+    const LANDING_DISTANCE: f64 = 1.0;
+
+    /// Derives the turn rate from the heading of the last and the current `AircraftState`
+    ///
+    /// A positive turn rate means a right turn. Returns zero if no previous sample is available or
+    /// if the time did not advance between the samples.
+    fn turn_rate(&mut self, state: &AircraftState) -> AngularVelocity {
+        let no_turn = AngularVelocity::new::<degree_per_second>(0.0);
+        let last_heading = self.last_heading.replace((state.timestamp, state.heading));
+
+        let (last_timestamp, last_heading) = match last_heading {
+            Some(sample) => sample,
+            None => return no_turn,
+        };
+        let dt = state.timestamp - last_timestamp;
+        if dt <= Time::new::<second>(0.0) {
+            return no_turn;
+        }
+
+        // wrap the heading change to (-180, 180] degrees, so that crossing north is no full turn
+        let mut change = (state.heading - last_heading).get::<degree>() % 360.0;
+        if change > 180.0 {
+            change -= 360.0;
+        } else if change <= -180.0 {
+            change += 360.0;
+        }
+
+        AngularVelocity::new::<degree_per_second>(change / dt.get::<second>())
+    }
+
+    /// Projects the flight path up to the caution look-ahead time and searches for the first
+    /// violation of the required terrain clearance
+    ///
+    /// The path keeps the current ground speed, climb rate and turn rate. Along the same track an
+    /// escape path is projected, on which the climb rate increases with the maximum climb rate
+    /// change until it reaches the maximum climb rate. Samples without terrain data are skipped,
+    /// while an unavailable terrain source aborts the search.
+    ///
+    /// On approach, the required clearance is reduced to `APPROACH_CLEARANCE`. It shrinks further
+    /// close to the nearest runway, and the path ends shortly before it. Without a runway in reach,
+    /// the path ends once it descends below `APPROACH_FLOOR` above the terrain below the aircraft.
+    fn search(
+        &self,
+        terrain_server: &dyn TerrainServer,
+        state: &AircraftState,
+        turn_rate: AngularVelocity,
+    ) -> Result<Option<Conflict>, TerrainError> {
+        let step = Time::new::<second>(Self::STEP);
+        let approach_clearance = Length::new::<foot>(Self::APPROACH_CLEARANCE);
+        let approach_floor =
+            state.altitude - state.altitude_ground + Length::new::<foot>(Self::APPROACH_FLOOR);
+
+        let mut time = Time::new::<second>(0.0);
+        let mut position = Position::from(state);
+        let mut heading = state.heading;
+        let mut escape_altitude = state.altitude;
+        let mut escape_climb_rate = state.climb_rate;
+        let mut conflict: Option<Conflict> = None;
+        let runway = self.approached_runway(&position);
+
+        while time < self.caution_time {
+            // advance all projections by one step
+            time += step;
+            position = position.destination(heading, state.speed_ground * step);
+            heading += Angle::new::<degree>(turn_rate.get::<degree_per_second>() * Self::STEP);
+            if escape_climb_rate < self.max_climbrate {
+                escape_climb_rate =
+                    (escape_climb_rate + self.max_climbrate_change * step).min(self.max_climbrate);
+            }
+            escape_altitude += escape_climb_rate * step;
+
+            let altitude = state.altitude + state.climb_rate * time;
+            let required_clearance = match runway {
+                _ if !self.approach => self.required_clearance,
+                Some(runway) => {
+                    let distance = position.distance(&runway.location);
+                    if distance < Length::new::<nautical_mile>(Self::LANDING_DISTANCE) {
+                        break;
+                    }
+                    let ratio = distance / Length::new::<nautical_mile>(Self::CLEARANCE_DISTANCE);
+                    self.required_clearance.min(approach_clearance) * ratio.value.min(1.0)
+                }
+                None if altitude < approach_floor => break,
+                None => self.required_clearance.min(approach_clearance),
+            };
+
+            match terrain_server.elevation(&position) {
+                Ok(elevation) => {
+                    if conflict.is_none() && altitude - elevation < required_clearance {
+                        conflict = Some(Conflict {
+                            time,
+                            escapable: true,
+                        });
+                    }
+
+                    if let Some(conflict) = conflict.as_mut() {
+                        conflict.escapable &= escape_altitude > elevation;
+                    }
+                }
+                Err(TerrainError::NoData) => {}
+                Err(TerrainError::Unavailable) => return Err(TerrainError::Unavailable),
+            }
+        }
+
+        Ok(conflict)
+    }
+
+    /// The runway closest to the present position while on approach, `None` if there is none
+    /// within reach or no runway data is available
+    fn approached_runway(&self, position: &Position) -> Option<Runway> {
+        if !self.approach {
+            return None;
+        }
+
+        let runway = self
+            .runway_server?
+            .nearest_runway(position)
+            .ok()
+            .flatten()?;
+        let max_distance = Length::new::<nautical_mile>(Self::MAX_RUNWAY_DISTANCE);
+        (position.distance(&runway.location) <= max_distance).then_some(runway)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::terrain_server::test::NullIsland;
+
+    /// Flat terrain at sea level, with a wall of the given elevation north of the given latitude
+    #[derive(Debug)]
+    struct Wall {
+        latitude: f64,
+        elevation: f64,
+    }
+
+    impl TerrainServer for Wall {
+        fn elevation(&self, position: &Position) -> Result<Length, TerrainError> {
+            let elevation = if position.latitude.get::<degree>() >= self.latitude {
+                self.elevation
+            } else {
+                0.0
+            };
+            Ok(Length::new::<foot>(elevation))
+        }
+    }
+
+    /// Terrain source which failed
+    #[derive(Debug)]
+    struct Failed;
+
+    impl TerrainServer for Failed {
+        fn elevation(&self, _position: &Position) -> Result<Length, TerrainError> {
+            Err(TerrainError::Unavailable)
+        }
+    }
+
+    static FLAT: Wall = Wall {
+        latitude: 90.0,
+        elevation: 0.0,
+    };
+
+    static LOW_WALL: Wall = Wall {
+        latitude: 0.0,
+        elevation: 3000.0,
+    };
+
+    static HIGH_WALL: Wall = Wall {
+        latitude: 0.0,
+        elevation: 30000.0,
+    };
+
+    /// Aircraft flying north at 3000 foot and 240 knots, reaching the equator in `time_to_wall`
+    fn state_south_of_wall(time_to_wall: f64) -> AircraftState {
+        let distance = Length::new::<nautical_mile>(240.0 * time_to_wall / 3600.0);
+        AircraftState {
+            timestamp: Time::new::<second>(0.0),
+            altitude: Length::new::<foot>(3000.0),
+            altitude_ground: Length::new::<foot>(3000.0),
+            position_lat: Angle::new::<uom::si::angle::radian>(
+                -(distance / Length::new::<uom::si::length::kilometer>(6371.0)).value,
+            ),
+            speed_ground: Velocity::new::<knot>(240.0),
+            ..Default::default()
+        }
+    }
+
+    fn flta(terrain_server: &dyn TerrainServer) -> Flta<'_> {
+        Flta::new(&TawsConfig {
+            terrain_server: Some(terrain_server),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn not_armed_without_terrain_data() {
+        let mut flta = Flta::new(&Default::default());
+        assert!(!flta.is_armed());
+        flta.flight_phase_changed(FlightPhase::Cruise);
+        assert!(!flta.is_armed());
+
+        assert_eq!(flta.process(&state_south_of_wall(10.0)), None);
+        assert!(!flta.is_failed());
+    }
+
+    #[test]
+    fn no_alert_with_unavailable_terrain() {
+        let mut flta = flta(&Failed);
+        assert!(!flta.is_failed());
+        assert_eq!(flta.process(&state_south_of_wall(10.0)), None);
+        assert!(flta.is_failed());
+    }
+
+    #[test]
+    fn no_alert_for_distant_terrain() {
+        let mut flta = flta(&LOW_WALL);
+        assert_eq!(flta.process(&state_south_of_wall(90.0)), None);
+        assert!(!flta.is_failed());
+    }
+
+    #[test]
+    fn caution_for_escapable_terrain() {
+        let mut flta = flta(&LOW_WALL);
+        assert_eq!(
+            flta.process(&state_south_of_wall(45.5)),
+            Some(AlertLevel::Caution)
+        );
+    }
+
+    #[test]
+    fn warning_for_close_terrain() {
+        let mut flta = flta(&LOW_WALL);
+        assert_eq!(
+            flta.process(&state_south_of_wall(15.5)),
+            Some(AlertLevel::Warning)
+        );
+    }
+
+    #[test]
+    fn warning_for_unescapable_terrain() {
+        let mut flta = flta(&HIGH_WALL);
+        assert_eq!(
+            flta.process(&state_south_of_wall(45.5)),
+            Some(AlertLevel::Warning)
+        );
+    }
+
+    #[test]
+    fn climbing_clears_terrain() {
+        let mut flta = flta(&LOW_WALL);
+        let state = AircraftState {
+            climb_rate: Velocity::new::<foot_per_minute>(3000.0),
+            ..state_south_of_wall(45.5)
+        };
+        assert_eq!(flta.process(&state), None);
+    }
+
+    #[test]
+    fn turning_away_from_terrain() {
+        let mut flta = flta(&LOW_WALL);
+        let mut state = state_south_of_wall(45.5);
+        assert_eq!(flta.process(&state), Some(AlertLevel::Caution));
+
+        // standard rate turn to the left, the aircraft heads south before reaching the wall
+        state.timestamp = Time::new::<second>(1.0);
+        state.heading = Angle::new::<degree>(357.0);
+        assert_eq!(flta.process(&state), None);
+    }
+
+    /// Aircraft flying north towards null island at 140 knots on a 3° glide path, descending at
+    /// 750 feet per minute
+    fn state_on_glide_path(distance: f64) -> AircraftState {
+        let height = Length::new::<nautical_mile>(distance) * libm::tan(3.0_f64.to_radians());
+        AircraftState {
+            altitude: height,
+            altitude_ground: height,
+            climb_rate: Velocity::new::<foot_per_minute>(-750.0),
+            position_lat: Angle::new::<uom::si::angle::radian>(
+                -(Length::new::<nautical_mile>(distance)
+                    / Length::new::<uom::si::length::kilometer>(6371.0))
+                .value,
+            ),
+            speed_ground: Velocity::new::<knot>(140.0),
+            landing_gear: GearPosition::Down,
+            ..Default::default()
+        }
+    }
+
+    fn approach(terrain_server: &'static dyn TerrainServer, runway: bool) -> Flta<'static> {
+        let mut flta = Flta::new(&TawsConfig {
+            terrain_server: Some(terrain_server),
+            runway_server: if runway { Some(&NullIsland) } else { None },
+            ..Default::default()
+        });
+        flta.flight_phase_changed(FlightPhase::Approach);
+        flta
+    }
+
+    #[test]
+    fn no_alert_on_stabilized_approach() {
+        // the path reaches the approach clearance within the caution time, but ends before the
+        // runway and passes it with the clearance reduced in proportion to the distance
+        let mut flta = approach(&FLAT, true);
+        assert_eq!(flta.process(&state_on_glide_path(3.0)), None);
+        assert!(!flta.is_failed());
+    }
+
+    #[test]
+    fn no_alert_on_approach_without_runway() {
+        let mut flta = approach(&FLAT, false);
+        for distance in [5.0, 3.0, 1.5, 0.5] {
+            assert_eq!(flta.process(&state_on_glide_path(distance)), None);
+        }
+    }
+
+    #[test]
+    fn alert_for_terrain_ahead_on_approach_without_runway() {
+        let mut flta = approach(&LOW_WALL, false);
+
+        // level at 1500 feet on the intermediate approach, one nautical mile before the wall
+        let state = AircraftState {
+            altitude: Length::new::<foot>(1500.0),
+            altitude_ground: Length::new::<foot>(1500.0),
+            climb_rate: Velocity::new::<foot_per_minute>(0.0),
+            ..state_on_glide_path(1.0)
+        };
+        assert_eq!(flta.process(&state), Some(AlertLevel::Warning));
+    }
+
+    #[test]
+    fn no_alert_on_ground() {
+        let mut flta = flta(&FLAT);
+        flta.flight_phase_changed(FlightPhase::Ground);
+
+        let state = AircraftState {
+            altitude: Length::new::<foot>(0.0),
+            altitude_ground: Length::new::<foot>(0.0),
+            speed_ground: Velocity::new::<knot>(15.0),
+            weight_on_wheels: Discrete::Active,
+            ..Default::default()
+        };
+        assert_eq!(flta.process(&state), None);
     }
 }
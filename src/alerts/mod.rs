@@ -1,7 +1,10 @@
 use core::fmt;
 
+use crate::flight_phase::FlightPhase;
+use crate::priorities::{AlertPriorities, ExternalAlert};
 use crate::types::{AircraftState, TawsConfig};
 
+mod bank_angle;
 mod ffac;
 mod flta;
 mod mode_1;
@@ -9,11 +12,15 @@ mod mode_2;
 mod mode_3;
 mod mode_4;
 mod mode_5;
+mod mode_6;
+mod mode_7;
 mod pda;
+mod tail_strike;
 
 pub mod functionalities {
     use super::*;
 
+    pub use bank_angle::*;
     pub use ffac::*;
     pub use flta::*;
     pub use mode_1::*;
@@ -21,7 +28,10 @@ pub mod functionalities {
     pub use mode_3::*;
     pub use mode_4::*;
     pub use mode_5::*;
+    pub use mode_6::*;
+    pub use mode_7::*;
     pub use pda::*;
+    pub use tail_strike::*;
 }
 
 /// Available alerts from the TAWS.
@@ -51,10 +61,90 @@ pub enum Alert {
 
     /// Excessive Downward Deviation from an ILS Glideslope or LPV/GLS Glidepath
     Mode5,
-    // TODO add more
+
+    /// Altitude Callouts
+    Mode6,
+
+    /// Reactive Windshear
+    Mode7,
+
+    /// Excessive Bank Angle
+    BankAngle,
+
+    /// Pitch Attitude close to a Tail Strike during Take-off Rotation or Flare
+    TailStrike,
 }
 impl Eq for Alert {}
 
+/// Get the priority of a n (Alert, AlertLevel) tupel
+///
+/// A low value means a high priority.
+#[deprecated(note = "use `AlertPriorities::priority` of `TawsConfig::alert_priorities` instead")]
+pub fn priority(alert: Alert, alert_level: AlertLevel) -> u8 {
+    AlertPriorities::DO_367
+        .priority(alert, alert_level, None)
+        .unwrap_or(u8::MAX)
+}
+
+impl Alert {
+    /// Number of different alerts
+    pub const COUNT: usize = 12;
+
+    /// All alerts
+    pub const ALL: [Alert; Alert::COUNT] = [
+        Alert::Flta,
+        Alert::Ffac,
+        Alert::Pda,
+        Alert::Mode1,
+        Alert::Mode2,
+        Alert::Mode3,
+        Alert::Mode4,
+        Alert::Mode5,
+        Alert::Mode6,
+        Alert::Mode7,
+        Alert::BankAngle,
+        Alert::TailStrike,
+    ];
+
+    /// The levels and sub-types with which the alert system of this alert may emit it
+    pub fn outputs(&self) -> &'static [(AlertLevel, Option<AlertSubType>)] {
+        use AlertLevel::*;
+        use AlertSubType::*;
+
+        match self {
+            Alert::Flta | Alert::Mode1 | Alert::Mode2 | Alert::Mode7 | Alert::BankAngle => {
+                &[(Warning, None), (Caution, None)]
+            }
+            Alert::Pda | Alert::Mode3 | Alert::TailStrike => &[(Caution, None)],
+            Alert::Ffac => &[(Annunciation, None)],
+            Alert::Mode4 => &[
+                (Caution, Some(TooLowTerrain)),
+                (Caution, Some(TooLowGear)),
+                (Caution, Some(TooLowFlaps)),
+            ],
+            Alert::Mode5 => &[
+                (Caution, Some(SoftGlideslope)),
+                (Caution, Some(HardGlideslope)),
+            ],
+            Alert::Mode6 => &[
+                (Annunciation, Some(Callout(self::Callout::Minimums))),
+                (
+                    Annunciation,
+                    Some(Callout(self::Callout::TwentyFiveHundred)),
+                ),
+                (Annunciation, Some(Callout(self::Callout::OneThousand))),
+                (Annunciation, Some(Callout(self::Callout::FiveHundred))),
+                (Annunciation, Some(Callout(self::Callout::OneHundred))),
+                (Annunciation, Some(Callout(self::Callout::Fifty))),
+                (Annunciation, Some(Callout(self::Callout::Forty))),
+                (Annunciation, Some(Callout(self::Callout::Thirty))),
+                (Annunciation, Some(Callout(self::Callout::Twenty))),
+                (Annunciation, Some(Callout(self::Callout::Ten))),
+            ],
+        }
+    }
+}
+
 /// Importance level of an alert
 ///
 /// Orderd by high priority to low priority (top to bottom)
@@ -75,73 +165,263 @@ pub enum AlertLevel {
 }
 impl Eq for AlertLevel {}
 
-/// Get the priority of a n (Alert, AlertLevel) tupel
-///
-/// A low value means a high priority.
-pub fn priority(alert: Alert, alert_level: AlertLevel) -> u8 {
-    use Alert::*;
-    use AlertLevel::*;
-
-    match (alert, alert_level) {
-        (Mode1, Warning) => 2,
-        (Mode2, Warning) => 3,
-        (Flta, Warning) => 6,
-        (Mode2, Caution) => 9,
-        (Flta, Caution) => 11,
-        (Mode4, Caution) => 13, // Terrain caution
-        (Pda, Caution) => 14,
-        //(Mode4, Caution)=>16 // Gear caution
-        //(Mode4, Caution)=>17 // Flaps caution
-        (Mode1, Caution) => 18,
-        (Mode3, Caution) => 19,
-        (Mode5, Caution) => 20,
-        _ => u8::MAX, // TODO is this a safe assumption
+/// Distinguishes the alerts of a functionality which emits more than one kind of alert on the same
+/// `AlertLevel`
+#[derive(Clone, Copy, Debug, PartialEq, Hash)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AlertSubType {
+    /// Mode 4 caution due to insufficient terrain clearance ("Too Low Terrain")
+    TooLowTerrain,
+
+    /// Mode 4 caution due to the landing gear not being down ("Too Low Gear")
+    TooLowGear,
+
+    /// Mode 4 caution due to the flaps not being in landing configuration ("Too Low Flaps")
+    TooLowFlaps,
+
+    /// Mode 5 caution for a moderate deviation below the glideslope, annunciated at reduced volume
+    SoftGlideslope,
+
+    /// Mode 5 caution for a large deviation below the glideslope, annunciated at full volume
+    HardGlideslope,
+
+    /// Mode 6 altitude callout
+    Callout(Callout),
+}
+impl Eq for AlertSubType {}
+
+/// Altitude callouts of Mode 6, each is annunciated when descending through its height above
+/// terrain
+#[derive(Clone, Copy, Debug, PartialEq, Hash)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Callout {
+    /// Reaching the decision height ("Minimums")
+    Minimums,
+
+    /// 2500 foot above terrain ("Twenty Five Hundred")
+    TwentyFiveHundred,
+
+    /// 1000 foot above terrain ("One Thousand")
+    OneThousand,
+
+    /// 500 foot above terrain ("Five Hundred")
+    FiveHundred,
+
+    /// 100 foot above terrain ("One Hundred")
+    OneHundred,
+
+    /// 50 foot above terrain ("Fifty")
+    Fifty,
+
+    /// 40 foot above terrain ("Forty")
+    Forty,
+
+    /// 30 foot above terrain ("Thirty")
+    Thirty,
+
+    /// 20 foot above terrain ("Twenty")
+    Twenty,
+
+    /// 10 foot above terrain ("Ten")
+    Ten,
+}
+impl Eq for Callout {}
+
+impl Callout {
+    /// Number of different callouts
+    pub const COUNT: usize = 10;
+
+    /// All callouts, from the highest to the lowest
+    pub const ALL: [Callout; Callout::COUNT] = [
+        Callout::Minimums,
+        Callout::TwentyFiveHundred,
+        Callout::OneThousand,
+        Callout::FiveHundred,
+        Callout::OneHundred,
+        Callout::Fifty,
+        Callout::Forty,
+        Callout::Thirty,
+        Callout::Twenty,
+        Callout::Ten,
+    ];
+
+    /// Height above terrain in foot at which the callout is annunciated, `None` for `Minimums` as
+    /// it depends on the decision height
+    pub fn height(&self) -> Option<f64> {
+        match self {
+            Callout::Minimums => None,
+            Callout::TwentyFiveHundred => Some(2500.0),
+            Callout::OneThousand => Some(1000.0),
+            Callout::FiveHundred => Some(500.0),
+            Callout::OneHundred => Some(100.0),
+            Callout::Fifty => Some(50.0),
+            Callout::Forty => Some(40.0),
+            Callout::Thirty => Some(30.0),
+            Callout::Twenty => Some(20.0),
+            Callout::Ten => Some(10.0),
+        }
     }
 }
 
-/// This is the maximum number of different alerts in an alert_state
-const ALERT_STATE_SIZE: usize = 8;
+/// This is the maximum number of different alerts in an alert_state, one for each `Alert`
+pub(crate) const ALERT_STATE_SIZE: usize = Alert::COUNT;
 
 /// Collection of a all alerts which are currently present in the TAWS
 #[derive(Debug, PartialEq)]
 #[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AlertState {
     /// Alerts which are not to be disclosed to the crew to avoid nuisance, but still where triggered
-    all_alerts: [Option<(Alert, AlertLevel)>; ALERT_STATE_SIZE],
+    all_alerts: [Option<(Alert, AlertLevel, Option<AlertSubType>)>; ALERT_STATE_SIZE],
+
+    /// Ranks the alerts, it is not recorded as it belongs to the configuration
+    #[cfg_attr(feature = "use-serde", serde(skip))]
+    priorities: AlertPriorities,
+
+    /// Alerts of systems outside of the TAWS which are active
+    external_alerts: [Option<ExternalAlert>; ExternalAlert::COUNT],
 }
 
 impl AlertState {
+    pub(crate) fn new(priorities: AlertPriorities) -> Self {
+        Self {
+            all_alerts: [None; ALERT_STATE_SIZE],
+            priorities,
+            external_alerts: [None; ExternalAlert::COUNT],
+        }
+    }
+
     pub fn alerts_total_count(&self) -> usize {
         self.all_alerts.iter().filter(|e| e.is_some()).count()
     }
 
+    /// Get the alert with the highest priority, `None` if no alert is present or an external alert
+    /// of higher priority is active
     pub fn priority_alert(&self) -> Option<(Alert, AlertLevel)> {
         self.all_alerts
             .iter()
             .filter_map(|o| {
-                o.map(|(alert, alert_level)| (priority(alert, alert_level), (alert, alert_level)))
+                o.map(|(alert, alert_level, sub_type)| {
+                    (
+                        self.priority(alert, alert_level, sub_type),
+                        (alert, alert_level),
+                    )
+                })
             })
             .min_by_key(|(p, _)| *p)
+            .filter(|(p, _)| !self.outranked(*p))
             .map(|(_, alert_stuff)| alert_stuff)
     }
 
+    /// Get an iterator to the active alerts of systems outside of the TAWS
+    pub fn external_alerts(&self) -> impl Iterator<Item = ExternalAlert> + '_ {
+        self.external_alerts.iter().flatten().copied()
+    }
+
+    /// Get the highest priority of the active external alerts, `None` if none is active
+    ///
+    /// External alerts missing in the prioritization of `TawsConfig::alert_priorities` are not
+    /// taken into account.
+    pub fn external_priority(&self) -> Option<u8> {
+        self.external_alerts()
+            .filter_map(|alert| self.priorities.external_priority(alert))
+            .min()
+    }
+
+    /// Returns whether a present alert is suppressed by an active external alert of higher
+    /// priority
+    ///
+    /// A suppressed alert stays in the `AlertState` for recording, but is neither the
+    /// `priority_alert` nor announced aurally.
+    pub fn is_suppressed(&self, alert: Alert) -> bool {
+        let external_priority = match self.external_priority() {
+            Some(external_priority) => external_priority,
+            None => return false,
+        };
+
+        self.level(alert)
+            .map(|level| self.priority(alert, level, self.sub_type(alert)) > external_priority)
+            .unwrap_or(false)
+    }
+
+    /// Get an iterator to the present alerts which are suppressed by an active external alert
+    pub fn suppressed(&self) -> impl Iterator<Item = Alert> + '_ {
+        self.all_alerts
+            .iter()
+            .flatten()
+            .map(|(alert, _, _)| *alert)
+            .filter(move |alert| self.is_suppressed(*alert))
+    }
+
+    /// Records the active external alerts, replacing those of the last `AircraftState`
+    pub(crate) fn set_external_alerts(&mut self, external_alerts: &[ExternalAlert]) {
+        self.external_alerts = [None; ExternalAlert::COUNT];
+        for alert in external_alerts {
+            if self.external_alerts().any(|existing| existing == *alert) {
+                continue;
+            }
+            if let Some(option) = self.external_alerts.iter_mut().find(|e| e.is_none()) {
+                *option = Some(*alert);
+            }
+        }
+    }
+
+    /// Returns whether a priority is lower than the one of an active external alert
+    fn outranked(&self, priority: u8) -> bool {
+        self.external_priority()
+            .map(|external_priority| priority > external_priority)
+            .unwrap_or(false)
+    }
+
+    /// Get the priority of an alert, a low value means a high priority
+    ///
+    /// Alerts missing in the prioritization of `TawsConfig::alert_priorities` rank last.
+    pub fn priority(&self, alert: Alert, level: AlertLevel, sub_type: Option<AlertSubType>) -> u8 {
+        self.priorities
+            .priority(alert, level, sub_type)
+            .unwrap_or(u8::MAX)
+    }
+
     /// Get an iterator to the alerts
     pub fn iter(&self) -> impl Iterator<Item = (Alert, AlertLevel)> {
         self.into_iter()
     }
 
+    /// Get the level of an alert, if the alert is present
+    pub fn level(&self, alert: Alert) -> Option<AlertLevel> {
+        self.all_alerts
+            .iter()
+            .flatten()
+            .find(|(existing_alert, _, _)| *existing_alert == alert)
+            .map(|(_, level, _)| *level)
+    }
+
+    /// Get the sub-type of an alert, if the alert is present and has a sub-type
+    pub fn sub_type(&self, alert: Alert) -> Option<AlertSubType> {
+        self.all_alerts
+            .iter()
+            .flatten()
+            .find(|(existing_alert, _, _)| *existing_alert == alert)
+            .and_then(|(_, _, sub_type)| *sub_type)
+    }
+
     /// updates internal alerts with new alerts, removing all old alerts. Prioritizes as well.
-    pub(crate) fn insert(&mut self, new_alert: Alert, new_alert_level: AlertLevel) {
+    pub(crate) fn insert(
+        &mut self,
+        new_alert: Alert,
+        new_alert_level: AlertLevel,
+        new_sub_type: Option<AlertSubType>,
+    ) {
         let mut already_present = false;
 
-        for (existing_alert, ref mut existing_alert_level) in
-            self.all_alerts.iter_mut().filter_map(|e| *e)
+        for (existing_alert, existing_alert_level, existing_sub_type) in
+            self.all_alerts.iter_mut().flatten()
         {
             // check if alert is already present
-            if existing_alert == new_alert {
+            if *existing_alert == new_alert {
                 // promote alerts of lower priority to higher priority
                 if new_alert_level < *existing_alert_level {
                     *existing_alert_level = new_alert_level;
+                    *existing_sub_type = new_sub_type;
                 }
                 already_present = true;
             }
@@ -150,7 +430,7 @@ impl AlertState {
         // lets find a free spot
         if !already_present {
             if let Some(option) = self.all_alerts.iter_mut().find(|e| e.is_none()) {
-                *option = Some((new_alert, new_alert_level));
+                *option = Some((new_alert, new_alert_level, new_sub_type));
             }
         }
     }
@@ -158,9 +438,7 @@ impl AlertState {
 
 impl Default for AlertState {
     fn default() -> Self {
-        Self {
-            all_alerts: [None; ALERT_STATE_SIZE],
-        }
+        Self::new(AlertPriorities::default())
     }
 }
 
@@ -184,20 +462,32 @@ impl IntoIterator for &AlertState {
     type Item = (Alert, AlertLevel);
     type IntoIter = AlertStateIter;
     fn into_iter(self) -> Self::IntoIter {
-        let mut alerts = self.all_alerts;
-        alerts.sort_by_key(|option| option.map(|(a, l)| priority(a, l)).unwrap_or(u8::MAX));
+        // look up each priority only once, the sorting compares them many times
+        let mut alerts = self.all_alerts.map(|option| {
+            let priority = option
+                .map(|(a, l, s)| self.priority(a, l, s))
+                .unwrap_or(u8::MAX);
+            (priority, option)
+        });
+        alerts.sort_by_key(|(priority, _)| *priority);
 
         AlertStateIter {
-            sorted_alerts: alerts,
+            sorted_alerts: alerts.map(|(_, option)| option.map(|(a, l, _)| (a, l))),
             index: 0,
         }
     }
 }
 
+/// Linear interpolation between two points, clamped to the y values of the points
+fn interpolate(x: f64, (x0, y0): (f64, f64), (x1, y1): (f64, f64)) -> f64 {
+    let x = x.clamp(x0, x1);
+    y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+}
+
 /// Trait which is to be fulfilled by all functionalities
-pub trait AlertSystem: fmt::Debug + Send {
+pub trait AlertSystem<'a>: fmt::Debug + Send {
     /// Allows this system to be instantiated
-    fn new(config: &TawsConfig) -> Self
+    fn new(config: &TawsConfig<'a>) -> Self
     where
         Self: Sized;
 
@@ -222,8 +512,37 @@ pub trait AlertSystem: fmt::Debug + Send {
     /// Returns whether this alarm is inhibited
     fn is_inhibited(&self) -> bool;
 
+    /// Returns whether this system lost a data source it depends on, e.g. the terrain database
+    ///
+    /// A failed system can not emit alerts, which the crew must be made aware of.
+    fn is_failed(&self) -> bool {
+        false
+    }
+
+    /// Checks the internal data of this system, e.g. its envelopes, for consistency
+    ///
+    /// This is part of the continuous self-test (DO-367 MOPS_002).
+    fn check_integrity(&self) -> bool {
+        true
+    }
+
+    /// Informs this system that the flight phase changed
+    ///
+    /// Systems which are only needed in some flight phases arm or disarm themselves here (DO-367
+    /// MOPS_007). This is called before `process` in the frame in which the phase changed, so it
+    /// does not override a manual `arm` or `disarm` until the phase changes again.
+    fn flight_phase_changed(&mut self, _phase: FlightPhase) {}
+
     /// Process a new AircraftState, emit alerts if appropiate
     fn process(&mut self, state: &AircraftState) -> Option<AlertLevel>;
+
+    /// Returns the sub-type of the alert emitted by the last call to `process`
+    ///
+    /// Only functionalities which emit more than one kind of alert on the same `AlertLevel` need
+    /// to implement this.
+    fn sub_type(&self) -> Option<AlertSubType> {
+        None
+    }
 }
 
 #[cfg(test)]
@@ -235,12 +554,70 @@ mod test {
         let mut alert_state = AlertState::default();
         let test_alerts = [(Alert::Mode3, AlertLevel::Caution)];
         for (new_alert, new_alert_level) in &test_alerts {
-            alert_state.insert(*new_alert, *new_alert_level);
+            alert_state.insert(*new_alert, *new_alert_level, None);
         }
 
         assert_eq!(test_alerts.len(), alert_state.alerts_total_count())
     }
 
+    #[test]
+    pub fn alert_state_ranks_sub_types() {
+        let mut alert_state = AlertState::default();
+        alert_state.insert(Alert::Mode3, AlertLevel::Caution, None);
+        alert_state.insert(
+            Alert::Mode4,
+            AlertLevel::Caution,
+            Some(AlertSubType::TooLowGear),
+        );
+        assert_eq!(
+            alert_state.priority_alert(),
+            Some((Alert::Mode4, AlertLevel::Caution))
+        );
+        assert_eq!(
+            alert_state.sub_type(Alert::Mode4),
+            Some(AlertSubType::TooLowGear)
+        );
+
+        alert_state.insert(Alert::Pda, AlertLevel::Caution, None);
+        assert_eq!(
+            alert_state.priority_alert(),
+            Some((Alert::Pda, AlertLevel::Caution))
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    pub fn priority_ranks_as_do_367() {
+        assert!(
+            priority(Alert::Mode1, AlertLevel::Warning)
+                < priority(Alert::Mode1, AlertLevel::Caution)
+        );
+        assert!(
+            priority(Alert::Mode7, AlertLevel::Warning)
+                < priority(Alert::Mode1, AlertLevel::Warning)
+        );
+    }
+
+    #[test]
+    pub fn external_alerts_suppress_lower_priorities() {
+        let mut alert_state = AlertState::default();
+        alert_state.insert(Alert::Mode3, AlertLevel::Caution, None);
+        alert_state.set_external_alerts(&[ExternalAlert::PredictiveWindshearWarning]);
+        assert!(alert_state.is_suppressed(Alert::Mode3));
+        assert_eq!(alert_state.priority_alert(), None);
+
+        alert_state.insert(Alert::Mode1, AlertLevel::Warning, None);
+        assert!(!alert_state.is_suppressed(Alert::Mode1));
+        assert_eq!(
+            alert_state.priority_alert(),
+            Some((Alert::Mode1, AlertLevel::Warning))
+        );
+        assert!(alert_state.suppressed().eq([Alert::Mode3]));
+
+        alert_state.set_external_alerts(&[]);
+        assert_eq!(alert_state.suppressed().next(), None);
+    }
+
     #[test]
     pub fn alert_state_usage() {
         let alts = AlertState::default();
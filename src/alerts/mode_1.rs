@@ -20,8 +20,8 @@ impl Default for Mode1 {
     }
 }
 
-impl AlertSystem for Mode1 {
-    fn new(_config: &TawsConfig) -> Self {
+impl<'a> AlertSystem<'a> for Mode1 {
+    fn new(_config: &TawsConfig<'a>) -> Self {
         Self {
             armed: true,
             inhibited: false,
@@ -29,6 +29,10 @@ impl AlertSystem for Mode1 {
     }
 
     fn process(&mut self, state: &AircraftState) -> Option<AlertLevel> {
+        if !self.armed {
+            return None;
+        }
+
         let altitude = state.altitude_ground.get::<foot>();
         let rod = -state.climb_rate.get::<foot_per_minute>();
 
@@ -48,6 +52,15 @@ impl AlertSystem for Mode1 {
     }
 
     arm_inhibit!();
+
+    fn check_integrity(&self) -> bool {
+        CAUTION_ENVELOPE.is_consistent()
+            && WARNING_ENVELOPE.is_consistent()
+            && CAUTION_ENVELOPE.encloses(&WARNING_ENVELOPE)
+            && CAUTION_ENVELOPE_STEEP_APPROACH.is_consistent()
+            && WARNING_ENVELOPE_STEEP_APPROACH.is_consistent()
+            && CAUTION_ENVELOPE_STEEP_APPROACH.encloses(&WARNING_ENVELOPE_STEEP_APPROACH)
+    }
 }
 
 lazy_static::lazy_static! {
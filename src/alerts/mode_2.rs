@@ -1,22 +1,211 @@
+use crate::envelope::Envelope;
+use crate::prelude::*;
+
 use super::*;
 
 #[derive(Debug)]
 pub struct Mode2 {
     armed: bool,
     inhibited: bool,
+
+    /// Timestamp and height above terrain of the last frame
+    last_sample: Option<(Time, Length)>,
+
+    /// Low pass filtered terrain closure rate, `None` until two successive samples are available
+    closure_rate: Option<Velocity>,
 }
 
-impl AlertSystem for Mode2 {
-    fn new(_config: &TawsConfig) -> Self {
+impl<'a> AlertSystem<'a> for Mode2 {
+    fn new(_config: &TawsConfig<'a>) -> Self {
         Self {
-            armed: false,
+            armed: true,
             inhibited: false,
+            last_sample: None,
+            closure_rate: None,
         }
     }
 
     arm_inhibit!();
 
-    fn process(&mut self, _state: &AircraftState) -> Option<AlertLevel> {
-        None
+    fn process(&mut self, state: &AircraftState) -> Option<AlertLevel> {
+        let closure_rate = self.closure_rate(state)?;
+
+        if !self.armed {
+            return None;
+        }
+
+        let altitude = state.altitude_ground.get::<foot>();
+        let closure_rate = closure_rate.get::<foot_per_minute>();
+
+        // Mode 2B is used when the flaps are in landing configuration, Mode 2A otherwise
+        match state.flaps == FlapPosition::Landing {
+            true if WARNING_ENVELOPE_2B.contains(closure_rate, altitude) => {
+                Some(AlertLevel::Warning)
+            }
+            true if CAUTION_ENVELOPE_2B.contains(closure_rate, altitude) => {
+                Some(AlertLevel::Caution)
+            }
+            false if altitude > Self::upper_limit_2a(state.speed_air).get::<foot>() => None,
+            false if WARNING_ENVELOPE_2A.contains(closure_rate, altitude) => {
+                Some(AlertLevel::Warning)
+            }
+            false if CAUTION_ENVELOPE_2A.contains(closure_rate, altitude) => {
+                Some(AlertLevel::Caution)
+            }
+            _ => None,
+        }
+    }
+
+    fn check_integrity(&self) -> bool {
+        CAUTION_ENVELOPE_2A.is_consistent()
+            && WARNING_ENVELOPE_2A.is_consistent()
+            && CAUTION_ENVELOPE_2A.encloses(&WARNING_ENVELOPE_2A)
+            && CAUTION_ENVELOPE_2B.is_consistent()
+            && WARNING_ENVELOPE_2B.is_consistent()
+            && CAUTION_ENVELOPE_2B.encloses(&WARNING_ENVELOPE_2B)
+    }
+}
+
+impl Mode2 {
+    /// Time constant of the filter for the terrain closure rate in seconds
+    const FILTER_TIME: f64 = 1.0;
+
+    /// Longest time between two samples in seconds, after which the closure rate starts over
+    const MAX_SAMPLE_GAP: f64 = 1.0;
+
+    /// Derives the terrain closure rate from the height above terrain of the last and the current
+    /// `AircraftState`
+    ///
+    /// The difference of successive samples follows every bit of radio altimeter noise and terrain
+    /// texture, so it is low pass filtered. A positive closure rate means that the aircraft
+    /// approaches the terrain. Returns `None` if no previous sample is available, or if the time did
+    /// not advance between the samples or advanced by more than `MAX_SAMPLE_GAP`.
+    fn closure_rate(&mut self, state: &AircraftState) -> Option<Velocity> {
+        let last_sample = self
+            .last_sample
+            .replace((state.timestamp, state.altitude_ground));
+
+        let (last_timestamp, last_altitude_ground) = last_sample?;
+        let dt = state.timestamp - last_timestamp;
+        if dt <= Time::new::<second>(0.0) || dt > Time::new::<second>(Self::MAX_SAMPLE_GAP) {
+            self.closure_rate = None;
+            return None;
+        }
+
+        let rate = (last_altitude_ground - state.altitude_ground) / dt;
+        let filter = dt.get::<second>() / (Self::FILTER_TIME + dt.get::<second>());
+        let closure_rate = match self.closure_rate {
+            Some(closure_rate) => closure_rate + (rate - closure_rate) * filter,
+            None => rate,
+        };
+
+        self.closure_rate = Some(closure_rate);
+        self.closure_rate
+    }
+
+    /// Upper boundary of the Mode 2A envelopes, which is extended with increasing airspeed
+    ///
+    /// Below 220 knots the upper boundary is at 1650 foot, above 310 knots it is at 2450 foot.
+    /// In between the boundary is interpolated linearly.
+    fn upper_limit_2a(speed_air: Velocity) -> Length {
+        let (low_speed, high_speed) = (220.0, 310.0);
+        let (low_limit, high_limit) = (1650.0, 2450.0);
+
+        let speed = speed_air.get::<knot>().clamp(low_speed, high_speed);
+        let limit =
+            low_limit + (high_limit - low_limit) * (speed - low_speed) / (high_speed - low_speed);
+
+        Length::new::<foot>(limit)
+    }
+}
+
+lazy_static::lazy_static! {
+
+        static ref CAUTION_ENVELOPE_2A: Envelope<4> = Envelope::new([
+            (2038.0, 30.0),
+            (3000.0, 1219.0),
+            (8000.0, 2450.0),
+            (8001.0, 2450.0),
+        ])
+        .unwrap();
+
+        static ref WARNING_ENVELOPE_2A: Envelope<4> = Envelope::new([
+            (2253.0, 30.0),
+            (3300.0, 1219.0),
+            (8500.0, 2450.0),
+            (8501.0, 2450.0),
+        ])
+        .unwrap();
+
+        static ref CAUTION_ENVELOPE_2B: Envelope<3> = Envelope::new([
+            (2253.0, 30.0),
+            (3000.0, 789.0),
+            (3001.0, 789.0),
+        ])
+        .unwrap();
+
+        static ref WARNING_ENVELOPE_2B: Envelope<3> = Envelope::new([
+            (2500.0, 30.0),
+            (3300.0, 789.0),
+            (3301.0, 789.0),
+        ])
+        .unwrap();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Aircraft at 1000 foot above terrain, with a height above terrain which changes by
+    /// `closure_rate` and is disturbed by up to three foot of noise
+    fn fly(
+        mode2: &mut Mode2,
+        closure_rate: f64,
+        seconds: usize,
+    ) -> std::vec::Vec<Option<AlertLevel>> {
+        (0..seconds * 10)
+            .map(|frame| {
+                let time = frame as f64 * 0.1;
+                let noise = 3.0 * libm::sin(frame as f64 * 2.3);
+                let state = AircraftState {
+                    timestamp: Time::new::<second>(time),
+                    altitude_ground: Length::new::<foot>(
+                        1000.0 - closure_rate / 60.0 * time + noise,
+                    ),
+                    speed_air: Velocity::new::<knot>(200.0),
+                    ..Default::default()
+                };
+                mode2.process(&state)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn no_alert_for_a_noisy_height() {
+        let mut mode2 = Mode2::new(&Default::default());
+        assert!(fly(&mut mode2, 0.0, 60).iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn alert_for_a_noisy_closure() {
+        let mut mode2 = Mode2::new(&Default::default());
+        let levels = fly(&mut mode2, 3400.0, 3);
+        assert!(levels[10..].iter().all(Option::is_some));
+    }
+
+    #[test]
+    fn closure_rate_starts_over_after_a_gap() {
+        let mut mode2 = Mode2::new(&Default::default());
+        let state = |time, height| AircraftState {
+            timestamp: Time::new::<second>(time),
+            altitude_ground: Length::new::<foot>(height),
+            ..Default::default()
+        };
+
+        mode2.closure_rate(&state(0.0, 1000.0));
+        assert!(mode2.closure_rate(&state(0.1, 995.0)).is_some());
+        assert_eq!(mode2.closure_rate(&state(5.0, 500.0)), None);
+        let closure_rate = mode2.closure_rate(&state(5.1, 495.0)).unwrap();
+        assert!((closure_rate.get::<foot_per_minute>() - 3000.0).abs() < 1e-6);
     }
 }
@@ -1,22 +1,79 @@
+use crate::envelope::Envelope;
+use crate::prelude::*;
+
 use super::*;
 
 #[derive(Debug)]
 pub struct Mode3 {
     armed: bool,
     inhibited: bool,
+    disarm_height: Length,
+
+    /// Highest altitude reached since the last take-off or go around
+    max_altitude: Option<Length>,
 }
 
-impl AlertSystem for Mode3 {
-    fn new(_config: &TawsConfig) -> Self {
+impl<'a> AlertSystem<'a> for Mode3 {
+    fn new(config: &TawsConfig<'a>) -> Self {
         Self {
             armed: false,
             inhibited: false,
+            disarm_height: config.mode3_disarm_height,
+            max_altitude: None,
         }
     }
 
     arm_inhibit!();
 
-    fn process(&mut self, _state: &AircraftState) -> Option<AlertLevel> {
+    fn flight_phase_changed(&mut self, phase: FlightPhase) {
+        match phase {
+            FlightPhase::Takeoff | FlightPhase::GoAround if !self.armed => {
+                self.armed = true;
+                self.max_altitude = None;
+            }
+            // the descent to land after a low circuit is no altitude loss after take-off
+            FlightPhase::Approach | FlightPhase::Landing => self.armed = false,
+            _ => {}
+        }
+    }
+
+    fn process(&mut self, state: &AircraftState) -> Option<AlertLevel> {
+        let below_disarm_height = state.altitude_ground < self.disarm_height;
+
+        if self.armed && !below_disarm_height {
+            self.armed = false;
+            self.max_altitude = None;
+        }
+
+        if !self.armed {
+            return None;
+        }
+
+        let max_altitude = self.max_altitude.get_or_insert(state.altitude);
+        if state.altitude > *max_altitude {
+            *max_altitude = state.altitude;
+        }
+
+        let altitude_loss = (*max_altitude - state.altitude).get::<foot>();
+        let altitude = state.altitude_ground.get::<foot>();
+
+        if CAUTION_ENVELOPE.contains(altitude_loss, altitude) {
+            return Some(AlertLevel::Caution);
+        }
+
         None
     }
+
+    fn check_integrity(&self) -> bool {
+        CAUTION_ENVELOPE.is_consistent()
+    }
+}
+
+lazy_static::lazy_static! {
+        static ref CAUTION_ENVELOPE: Envelope<3> = Envelope::new([
+            (10.0, 30.0),
+            (143.0, 1500.0),
+            (144.0, 1500.0),
+        ])
+        .unwrap();
 }
@@ -1,22 +1,128 @@
+use crate::prelude::*;
+
 use super::*;
 
 #[derive(Debug)]
 pub struct Mode4 {
     armed: bool,
     inhibited: bool,
+    sub_type: Option<AlertSubType>,
+
+    /// `true` while the aircraft is in the take-off or go around phase, in which Mode 4C is used
+    takeoff: bool,
+
+    /// Highest height above terrain reached since the last take-off or go around
+    max_altitude_ground: Length,
 }
 
-impl AlertSystem for Mode4 {
-    fn new(_config: &TawsConfig) -> Self {
+impl<'a> AlertSystem<'a> for Mode4 {
+    fn new(_config: &TawsConfig<'a>) -> Self {
         Self {
-            armed: false,
+            armed: true,
             inhibited: false,
+            sub_type: None,
+            takeoff: false,
+            max_altitude_ground: Length::new::<foot>(0.0),
         }
     }
 
     arm_inhibit!();
 
-    fn process(&mut self, _state: &AircraftState) -> Option<AlertLevel> {
+    fn flight_phase_changed(&mut self, phase: FlightPhase) {
+        self.takeoff = matches!(phase, FlightPhase::Takeoff | FlightPhase::GoAround);
+        self.max_altitude_ground = Length::new::<foot>(0.0);
+    }
+
+    fn process(&mut self, state: &AircraftState) -> Option<AlertLevel> {
+        if self.takeoff && state.altitude_ground > self.max_altitude_ground {
+            self.max_altitude_ground = state.altitude_ground;
+        }
+
+        self.sub_type = if !self.armed {
+            None
+        } else if self.takeoff {
+            Self::mode_4c(state, self.max_altitude_ground)
+        } else if state.landing_gear == GearPosition::Up {
+            Self::mode_4a(state)
+        } else if state.landing_gear == GearPosition::Down && Self::flaps_up(state) {
+            Self::mode_4b(state)
+        } else {
+            None
+        };
+
+        self.sub_type.map(|_| AlertLevel::Caution)
+    }
+
+    fn sub_type(&self) -> Option<AlertSubType> {
+        self.sub_type
+    }
+}
+
+impl Mode4 {
+    /// Lower boundary of all Mode 4 envelopes
+    const MIN_HEIGHT: f64 = 30.0;
+
+    /// `true` if the flaps are known to be out of landing configuration
+    fn flaps_up(state: &AircraftState) -> bool {
+        matches!(state.flaps, FlapPosition::Up | FlapPosition::Intermediate)
+    }
+
+    /// Mode 4A: landing gear not down
+    fn mode_4a(state: &AircraftState) -> Option<AlertSubType> {
+        let altitude = state.altitude_ground.get::<foot>();
+        let speed = state.speed_air.get::<knot>();
+
+        if speed < 190.0 && (Self::MIN_HEIGHT..500.0).contains(&altitude) {
+            return Some(AlertSubType::TooLowGear);
+        }
+
+        let upper_boundary = interpolate(speed, (190.0, 500.0), (250.0, 1000.0));
+        if speed >= 190.0 && (Self::MIN_HEIGHT..upper_boundary).contains(&altitude) {
+            return Some(AlertSubType::TooLowTerrain);
+        }
+
+        None
+    }
+
+    /// Mode 4B: landing gear down, but flaps not in landing configuration
+    fn mode_4b(state: &AircraftState) -> Option<AlertSubType> {
+        let altitude = state.altitude_ground.get::<foot>();
+        let speed = state.speed_air.get::<knot>();
+
+        if speed < 159.0 && (Self::MIN_HEIGHT..245.0).contains(&altitude) {
+            return Some(AlertSubType::TooLowFlaps);
+        }
+
+        let upper_boundary = interpolate(speed, (159.0, 245.0), (250.0, 1000.0));
+        if speed >= 159.0 && (Self::MIN_HEIGHT..upper_boundary).contains(&altitude) {
+            return Some(AlertSubType::TooLowTerrain);
+        }
+
+        None
+    }
+
+    /// Mode 4C: insufficient terrain clearance during take-off or go around, while either the
+    /// landing gear is not down or the flaps are not in landing configuration
+    ///
+    /// Inputs which are not available never trigger Mode 4C.
+    ///
+    /// The minimum terrain clearance is 75% of the highest height above terrain reached since the
+    /// take-off, but never more than the speed dependent upper boundary of Mode 4A.
+    fn mode_4c(state: &AircraftState, max_altitude_ground: Length) -> Option<AlertSubType> {
+        if state.landing_gear != GearPosition::Up && !Self::flaps_up(state) {
+            return None;
+        }
+
+        let altitude = state.altitude_ground.get::<foot>();
+        let speed = state.speed_air.get::<knot>();
+
+        let upper_boundary = interpolate(speed, (190.0, 500.0), (250.0, 1000.0));
+        let min_terrain_clearance = (0.75 * max_altitude_ground.get::<foot>()).min(upper_boundary);
+
+        if (Self::MIN_HEIGHT..min_terrain_clearance).contains(&altitude) {
+            return Some(AlertSubType::TooLowTerrain);
+        }
+
         None
     }
 }
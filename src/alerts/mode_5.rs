@@ -1,22 +1,93 @@
+use crate::prelude::*;
+
 use super::*;
 
 #[derive(Debug)]
 pub struct Mode5 {
     armed: bool,
     inhibited: bool,
+    sub_type: Option<AlertSubType>,
+
+    /// `true` if the crew cancelled the glideslope alerting for the current approach
+    cancelled: bool,
 }
 
-impl AlertSystem for Mode5 {
-    fn new(_config: &TawsConfig) -> Self {
+impl<'a> AlertSystem<'a> for Mode5 {
+    fn new(_config: &TawsConfig<'a>) -> Self {
         Self {
-            armed: false,
+            armed: true,
             inhibited: false,
+            sub_type: None,
+            cancelled: false,
         }
     }
 
     arm_inhibit!();
 
-    fn process(&mut self, _state: &AircraftState) -> Option<AlertLevel> {
-        None
+    fn flight_phase_changed(&mut self, phase: FlightPhase) {
+        match phase {
+            FlightPhase::Approach => self.armed = true,
+            FlightPhase::Takeoff => self.armed = false,
+            _ => {}
+        }
+    }
+
+    fn process(&mut self, state: &AircraftState) -> Option<AlertLevel> {
+        let altitude = state.altitude_ground.get::<foot>();
+
+        // The glideslope cancel lasts until the aircraft either climbs away or lands
+        if !(Self::MIN_HEIGHT..=Self::CANCEL_RESET_HEIGHT).contains(&altitude) {
+            self.cancelled = false;
+        }
+
+        self.sub_type = match state.glideslope_deviation {
+            _ if !self.armed || self.cancelled => None,
+            _ if state.landing_gear == GearPosition::Up => None,
+            Some(deviation) if Self::hard_envelope_contains(deviation, altitude) => {
+                Some(AlertSubType::HardGlideslope)
+            }
+            Some(deviation) if Self::soft_envelope_contains(deviation, altitude) => {
+                Some(AlertSubType::SoftGlideslope)
+            }
+            _ => None,
+        };
+
+        self.sub_type.map(|_| AlertLevel::Caution)
+    }
+
+    fn sub_type(&self) -> Option<AlertSubType> {
+        self.sub_type
+    }
+}
+
+impl Mode5 {
+    /// Lower boundary of both Mode 5 envelopes
+    const MIN_HEIGHT: f64 = 30.0;
+
+    /// Height above terrain above which a glideslope cancel is reset
+    const CANCEL_RESET_HEIGHT: f64 = 2000.0;
+
+    /// Cancel the glideslope alerting until the next approach
+    ///
+    /// The cancel is reset once the aircraft climbs above 2000 foot or descends below 30 foot
+    /// above terrain.
+    pub fn cancel(&mut self) {
+        self.cancelled = true;
+    }
+
+    /// The soft envelope covers deviations of more than 1.3 dots below the glideslope between
+    /// 150 and 1000 foot above terrain. Below 150 foot the required deviation increases to 2.98 dots
+    /// at 30 foot.
+    fn soft_envelope_contains(deviation: f64, altitude: f64) -> bool {
+        (Self::MIN_HEIGHT..=1000.0).contains(&altitude)
+            && deviation >= interpolate(altitude, (30.0, 2.98), (150.0, 1.3))
+    }
+
+    /// The hard envelope covers deviations of more than 2 dots below the glideslope between 150
+    /// and 300 foot above terrain. Below 150 foot the required deviation increases to 3.68 dots at
+    /// 30 foot.
+    fn hard_envelope_contains(deviation: f64, altitude: f64) -> bool {
+        (Self::MIN_HEIGHT..=300.0).contains(&altitude)
+            && deviation >= interpolate(altitude, (30.0, 3.68), (150.0, 2.0))
     }
 }
@@ -0,0 +1,181 @@
+use crate::prelude::*;
+
+use super::*;
+
+#[derive(Debug)]
+pub struct Mode6<'a> {
+    armed: bool,
+    inhibited: bool,
+    sub_type: Option<AlertSubType>,
+    callouts: &'a [Callout],
+    decision_height: Option<Length>,
+    last_height: Option<Length>,
+
+    /// `true` for each callout which was annunciated during the current approach, indexed by the
+    /// `Callout` discriminant
+    latched: [bool; Callout::COUNT],
+}
+
+impl<'a> AlertSystem<'a> for Mode6<'a> {
+    fn new(config: &TawsConfig<'a>) -> Self {
+        Self {
+            armed: true,
+            inhibited: false,
+            sub_type: None,
+            callouts: config.mode6_callouts,
+            decision_height: config.decision_height,
+            last_height: None,
+            latched: [false; Callout::COUNT],
+        }
+    }
+
+    arm_inhibit!();
+
+    fn process(&mut self, state: &AircraftState) -> Option<AlertLevel> {
+        let height = state.altitude_ground;
+        let last_height = self.last_height.replace(height);
+        let rearm_margin = Length::new::<foot>(Self::REARM_MARGIN);
+
+        // the lowest callout crossed since the last frame
+        let mut crossed: Option<(Length, Callout)> = None;
+
+        for &callout in self.callouts {
+            let callout_height = match callout.height() {
+                Some(callout_height) => Length::new::<foot>(callout_height),
+                None => match self.decision_height {
+                    Some(decision_height) => decision_height,
+                    None => continue,
+                },
+            };
+
+            let latched = &mut self.latched[callout as usize];
+            if height > callout_height + rearm_margin {
+                *latched = false;
+            }
+
+            let descended_through = matches!(
+                last_height,
+                Some(last_height) if last_height >= callout_height && height < callout_height
+            );
+            if descended_through && !*latched {
+                *latched = true;
+                let lowest = !matches!(crossed, Some((lowest, _)) if lowest <= callout_height);
+                if lowest {
+                    crossed = Some((callout_height, callout));
+                }
+            }
+        }
+
+        self.sub_type = crossed
+            .filter(|_| self.armed)
+            .map(|(_, callout)| AlertSubType::Callout(callout));
+
+        self.sub_type.map(|_| AlertLevel::Annunciation)
+    }
+
+    fn sub_type(&self) -> Option<AlertSubType> {
+        self.sub_type
+    }
+}
+
+impl Mode6<'_> {
+    /// Height in foot above its callout height the aircraft has to climb, before a callout is
+    /// annunciated again
+    const REARM_MARGIN: f64 = 50.0;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::vec::Vec;
+
+    /// Descends from `from` to `to` foot above terrain in steps of one foot, returning the
+    /// annunciated callouts
+    fn descend(mode6: &mut Mode6, from: i32, to: i32) -> Vec<Callout> {
+        (to..=from)
+            .rev()
+            .filter_map(|height| {
+                let state = AircraftState {
+                    altitude_ground: Length::new::<foot>(f64::from(height)),
+                    ..Default::default()
+                };
+                mode6.process(&state)?;
+                match mode6.sub_type() {
+                    Some(AlertSubType::Callout(callout)) => Some(callout),
+                    sub_type => panic!("unexpected sub-type {:?}", sub_type),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn all_callouts_once() {
+        let mut mode6 = Mode6::new(&TawsConfig::default());
+
+        assert_eq!(descend(&mut mode6, 3000, 0), &Callout::ALL[1..]);
+
+        // bouncing after the touchdown does not repeat the callouts
+        assert_eq!(descend(&mut mode6, 30, 0), &[]);
+    }
+
+    #[test]
+    fn callouts_loaded_at_runtime() {
+        let callouts: Vec<_> = std::vec![Callout::FiveHundred];
+        let mut mode6 = Mode6::new(&TawsConfig {
+            mode6_callouts: &callouts,
+            ..Default::default()
+        });
+
+        assert_eq!(descend(&mut mode6, 1000, 0), &[Callout::FiveHundred]);
+    }
+
+    #[test]
+    fn minimums_at_decision_height() {
+        let mut mode6 = Mode6::new(&TawsConfig {
+            mode6_callouts: &[Callout::Minimums, Callout::FiveHundred, Callout::OneHundred],
+            decision_height: Some(Length::new::<foot>(200.0)),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            descend(&mut mode6, 1000, 0),
+            &[Callout::FiveHundred, Callout::Minimums, Callout::OneHundred]
+        );
+    }
+
+    #[test]
+    fn rearm_after_go_around() {
+        let mut mode6 = Mode6::new(&TawsConfig::default());
+
+        assert_eq!(descend(&mut mode6, 600, 480), &[Callout::FiveHundred]);
+
+        // hovering around the callout height does not repeat it
+        descend(&mut mode6, 520, 520);
+        assert_eq!(descend(&mut mode6, 520, 480), &[]);
+
+        // climbing well above the callout height starts a new approach
+        descend(&mut mode6, 560, 560);
+        assert_eq!(descend(&mut mode6, 560, 480), &[Callout::FiveHundred]);
+    }
+
+    #[test]
+    fn lowest_crossed_callout_wins() {
+        let mut mode6 = Mode6::new(&TawsConfig::default());
+
+        descend(&mut mode6, 60, 60);
+        descend(&mut mode6, 25, 25);
+        assert_eq!(
+            mode6.sub_type(),
+            Some(AlertSubType::Callout(Callout::Thirty))
+        );
+        assert_eq!(descend(&mut mode6, 25, 0), &[Callout::Twenty, Callout::Ten]);
+    }
+
+    #[test]
+    fn no_callouts_when_disarmed() {
+        let mut mode6 = Mode6::new(&TawsConfig::default());
+        mode6.disarm();
+
+        assert_eq!(descend(&mut mode6, 3000, 0), &[]);
+    }
+}
@@ -0,0 +1,194 @@
+use uom::si::{acceleration::meter_per_second_squared, angle::radian, velocity::meter_per_second};
+
+use crate::prelude::*;
+
+use super::*;
+
+#[derive(Debug)]
+pub struct Mode7 {
+    armed: bool,
+    inhibited: bool,
+    max_height: Length,
+    decreasing_threshold: f64,
+    increasing_threshold: f64,
+
+    /// Timestamp and headwind of the last frame
+    last_headwind: Option<(Time, Velocity)>,
+
+    /// Low pass filtered rate of change of the headwind
+    headwind_rate: Acceleration,
+
+    /// Low pass filtered vertical speed of the air mass, positive for an updraft
+    updraft: Velocity,
+
+    /// Long term average of the vertical speed of the air mass
+    updraft_bias: Velocity,
+}
+
+impl<'a> AlertSystem<'a> for Mode7 {
+    fn new(config: &TawsConfig<'a>) -> Self {
+        Self {
+            armed: true,
+            inhibited: false,
+            max_height: config.mode7_max_height,
+            decreasing_threshold: config.mode7_decreasing_threshold,
+            increasing_threshold: config.mode7_increasing_threshold,
+            last_headwind: None,
+            headwind_rate: Acceleration::new::<meter_per_second_squared>(0.0),
+            updraft: Velocity::new::<meter_per_second>(0.0),
+            updraft_bias: Velocity::new::<meter_per_second>(0.0),
+        }
+    }
+
+    arm_inhibit!();
+
+    fn process(&mut self, state: &AircraftState) -> Option<AlertLevel> {
+        let shear = self.shear_factor(state)?;
+
+        if !self.armed
+            || state.altitude_ground > self.max_height
+            || state.speed_air < Velocity::new::<knot>(Self::MIN_AIRSPEED)
+        {
+            return None;
+        }
+
+        if shear >= self.decreasing_threshold {
+            Some(AlertLevel::Warning)
+        } else if shear <= -self.increasing_threshold {
+            Some(AlertLevel::Caution)
+        } else {
+            None
+        }
+    }
+}
+
+impl Mode7 {
+    /// Airspeed below which the aircraft is considered to be on ground
+    const MIN_AIRSPEED: f64 = 40.0;
+
+    /// Time constant of the filters for the headwind rate and the updraft in seconds
+    const FILTER_TIME: f64 = 1.0;
+
+    /// Time constant of the long term average of the updraft in seconds
+    const BIAS_TIME: f64 = 30.0;
+
+    /// Updates the wind estimates and derives the shear factor (F-factor) from them
+    ///
+    /// The headwind is the difference between airspeed and ground speed, so a change of the
+    /// airspeed which is not matched by the ground speed is a horizontal shear. The angle of attack
+    /// is not known, so the vertical speed of the air mass is estimated from the difference between
+    /// the climb rate and the vertical speed the pitch attitude yields at the current airspeed. The
+    /// steady offset this leaves behind is removed by subtracting its long term average.
+    ///
+    /// A positive shear factor stands for a loss of performance, i.e. a decreasing headwind or a
+    /// downdraft. Returns `None` if no previous sample is available or if the time did not advance
+    /// between the samples.
+    fn shear_factor(&mut self, state: &AircraftState) -> Option<f64> {
+        let headwind = state.speed_air - state.speed_ground;
+        let updraft = state.climb_rate - state.speed_air * libm::sin(state.pitch.get::<radian>());
+
+        let (last_timestamp, last_headwind) =
+            match self.last_headwind.replace((state.timestamp, headwind)) {
+                Some(sample) => sample,
+                None => {
+                    self.updraft = updraft;
+                    self.updraft_bias = updraft;
+                    return None;
+                }
+            };
+
+        let dt = state.timestamp - last_timestamp;
+        if dt <= Time::new::<second>(0.0) {
+            return None;
+        }
+
+        let filter = |time_constant: f64| {
+            let dt = dt.get::<second>();
+            dt / (time_constant + dt)
+        };
+
+        self.headwind_rate +=
+            ((headwind - last_headwind) / dt - self.headwind_rate) * filter(Self::FILTER_TIME);
+        self.updraft += (updraft - self.updraft) * filter(Self::FILTER_TIME);
+        self.updraft_bias += (updraft - self.updraft_bias) * filter(Self::BIAS_TIME);
+
+        let gravity = Acceleration::new::<meter_per_second_squared>(9.80665);
+        let airspeed = state
+            .speed_air
+            .max(Velocity::new::<knot>(Self::MIN_AIRSPEED));
+
+        Some((-self.headwind_rate / gravity - (self.updraft - self.updraft_bias) / airspeed).value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Aircraft descending on a three degree glide path at 140 knots, without any wind
+    fn approach(time: f64) -> AircraftState {
+        let speed = Velocity::new::<knot>(140.0);
+        AircraftState {
+            timestamp: Time::new::<second>(time),
+            altitude_ground: Length::new::<foot>(800.0),
+            climb_rate: -speed * libm::sin(3.0_f64.to_radians()),
+            speed_air: speed,
+            speed_ground: speed,
+            pitch: Angle::new::<degree>(2.0),
+            ..Default::default()
+        }
+    }
+
+    /// Processes one minute of frames, ten per second, and returns the last alert level
+    fn fly(mode7: &mut Mode7, mut shape: impl FnMut(&mut AircraftState)) -> Option<AlertLevel> {
+        let mut level = None;
+        for frame in 0..600 {
+            let mut state = approach(frame as f64 * 0.1);
+            shape(&mut state);
+            level = mode7.process(&state);
+        }
+        level
+    }
+
+    #[test]
+    fn no_alert_on_a_steady_approach() {
+        let mut mode7 = Mode7::new(&Default::default());
+        assert_eq!(fly(&mut mode7, |_| {}), None);
+    }
+
+    #[test]
+    fn warning_for_a_downdraft() {
+        let mut mode7 = Mode7::new(&Default::default());
+        fly(&mut mode7, |_| {});
+
+        let downdraft = Velocity::new::<foot_per_minute>(2000.0);
+        let mut state = approach(60.0);
+        let mut level = None;
+        for frame in 0..30 {
+            state.timestamp += Time::new::<second>(0.1);
+            state.climb_rate =
+                approach(0.0).climb_rate - downdraft * (frame as f64 / 10.0).min(1.0);
+            level = mode7.process(&state);
+        }
+        assert_eq!(level, Some(AlertLevel::Warning));
+    }
+
+    #[test]
+    fn caution_for_an_increasing_headwind() {
+        let mut mode7 = Mode7::new(&Default::default());
+        let level = fly(&mut mode7, |state| {
+            state.speed_air += Velocity::new::<knot>(3.0) * state.timestamp.get::<second>()
+        });
+        assert_eq!(level, Some(AlertLevel::Caution));
+    }
+
+    #[test]
+    fn no_alert_above_max_height() {
+        let mut mode7 = Mode7::new(&Default::default());
+        let level = fly(&mut mode7, |state| {
+            state.altitude_ground = Length::new::<foot>(2000.0);
+            state.speed_ground += Velocity::new::<knot>(3.0) * state.timestamp.get::<second>()
+        });
+        assert_eq!(level, None);
+    }
+}
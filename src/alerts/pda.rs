@@ -1,22 +1,146 @@
+use uom::si::length::nautical_mile;
+
+use crate::envelope::Envelope;
+use crate::prelude::*;
+
 use super::*;
 
 #[derive(Debug)]
-pub struct Pda {
+pub struct Pda<'a> {
     armed: bool,
     inhibited: bool,
+    runway_server: Option<&'a dyn RunwayServer>,
+    runway_failed: bool,
 }
 
-impl AlertSystem for Pda {
-    fn new(_config: &TawsConfig) -> Self {
+impl<'a> AlertSystem<'a> for Pda<'a> {
+    fn new(config: &TawsConfig<'a>) -> Self {
         Self {
-            armed: false,
+            // PDA is not installed without runway data, which is no failure
+            armed: config.runway_server.is_some(),
             inhibited: false,
+            runway_server: config.runway_server,
+            runway_failed: false,
         }
     }
 
     arm_inhibit!();
 
-    fn process(&mut self, _state: &AircraftState) -> Option<AlertLevel> {
+    fn flight_phase_changed(&mut self, phase: FlightPhase) {
+        match phase {
+            FlightPhase::Approach => self.armed = self.runway_server.is_some(),
+            FlightPhase::Takeoff => self.armed = false,
+            _ => {}
+        }
+    }
+
+    fn process(&mut self, state: &AircraftState) -> Option<AlertLevel> {
+        if !self.armed
+            || state.terrain_inhibit == Discrete::Active
+            || state.climb_rate >= Velocity::new::<foot_per_minute>(0.0)
+        {
+            return None;
+        }
+
+        let position = Position::from(state);
+        let nearest = self.runway_server?.nearest_runway(&position);
+        self.runway_failed = nearest.is_err();
+        let runway = nearest.ok().flatten()?;
+
+        let distance = position.distance(&runway.location).get::<nautical_mile>();
+        let altitude = state.altitude_ground.get::<foot>();
+
+        if distance <= MAX_DISTANCE && CAUTION_ENVELOPE.contains(distance, altitude) {
+            return Some(AlertLevel::Caution);
+        }
+
         None
     }
+
+    fn is_failed(&self) -> bool {
+        self.runway_failed
+    }
+
+    fn check_integrity(&self) -> bool {
+        CAUTION_ENVELOPE.is_consistent()
+    }
+}
+
+/// Distance to the nearest runway in nautical miles beyond which PDA does not alert
+const MAX_DISTANCE: f64 = 15.0;
+
+lazy_static::lazy_static! {
+        static ref CAUTION_ENVELOPE: Envelope<5> = Envelope::new([
+            (1.0, 30.0),
+            (2.0, 150.0),
+            (5.0, 400.0),
+            (10.0, 700.0),
+            (15.0, 700.0),
+        ])
+        .unwrap();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Runway database which can not be accessed
+    #[derive(Debug)]
+    struct Failed;
+
+    impl RunwayServer for Failed {
+        fn nearest_runway(&self, _position: &Position) -> Result<Option<Runway>, TerrainError> {
+            Err(TerrainError::Unavailable)
+        }
+    }
+
+    /// Runway database without any runway
+    #[derive(Debug)]
+    struct Empty;
+
+    impl RunwayServer for Empty {
+        fn nearest_runway(&self, _position: &Position) -> Result<Option<Runway>, TerrainError> {
+            Ok(None)
+        }
+    }
+
+    fn descending() -> AircraftState {
+        AircraftState {
+            altitude_ground: Length::new::<foot>(300.0),
+            climb_rate: Velocity::new::<foot_per_minute>(-1000.0),
+            ..Default::default()
+        }
+    }
+
+    fn pda(runway_server: &dyn RunwayServer) -> Pda<'_> {
+        Pda::new(&TawsConfig {
+            runway_server: Some(runway_server),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn not_installed_without_runway_data() {
+        let mut pda = Pda::new(&Default::default());
+        pda.flight_phase_changed(FlightPhase::Approach);
+        assert!(!pda.is_armed());
+        assert_eq!(pda.process(&descending()), None);
+        assert!(!pda.is_failed());
+    }
+
+    #[test]
+    fn failed_with_unavailable_runway_data() {
+        let mut pda = pda(&Failed);
+        assert!(pda.is_armed());
+        assert!(!pda.is_failed());
+        assert_eq!(pda.process(&descending()), None);
+        assert!(pda.is_failed());
+    }
+
+    #[test]
+    fn not_failed_without_nearby_runway() {
+        let mut pda = pda(&Empty);
+        assert_eq!(pda.process(&descending()), None);
+        assert!(!pda.is_failed());
+    }
 }
@@ -0,0 +1,91 @@
+use crate::prelude::*;
+
+use super::*;
+
+#[derive(Debug)]
+pub struct TailStrike {
+    armed: bool,
+    inhibited: bool,
+    pitch_limit: Option<Angle>,
+}
+
+impl<'a> AlertSystem<'a> for TailStrike {
+    fn new(config: &TawsConfig<'a>) -> Self {
+        // the pitch limit depends on the aircraft, so there is no sensible default
+        Self {
+            armed: config.tail_strike_pitch_limit.is_some(),
+            inhibited: false,
+            pitch_limit: config.tail_strike_pitch_limit,
+        }
+    }
+
+    arm_inhibit!();
+
+    fn process(&mut self, state: &AircraftState) -> Option<AlertLevel> {
+        let pitch_limit = self.pitch_limit?.get::<degree>();
+        let pitch = libm::remainder(state.pitch.get::<degree>(), 360.0);
+        let altitude = state.altitude_ground.get::<foot>();
+
+        (self.armed && altitude < Self::MAX_HEIGHT && pitch >= pitch_limit - Self::MARGIN)
+            .then_some(AlertLevel::Caution)
+    }
+}
+
+impl TailStrike {
+    /// Height above terrain up to which the tail can touch the ground during rotation or flare
+    const MAX_HEIGHT: f64 = 30.0;
+
+    /// Degrees below the pitch limit at which the caution is given
+    const MARGIN: f64 = 2.0;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn state(height: f64, pitch: f64) -> AircraftState {
+        AircraftState {
+            altitude: Length::new::<foot>(height),
+            altitude_ground: Length::new::<foot>(height),
+            pitch: Angle::new::<degree>(pitch),
+            ..Default::default()
+        }
+    }
+
+    fn tail_strike() -> TailStrike {
+        TailStrike::new(&TawsConfig {
+            tail_strike_pitch_limit: Some(Angle::new::<degree>(11.0)),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn not_armed_without_pitch_limit() {
+        let mut tail_strike = TailStrike::new(&Default::default());
+        assert!(!tail_strike.is_armed());
+
+        tail_strike.arm();
+        assert_eq!(tail_strike.process(&state(5.0, 15.0)), None);
+    }
+
+    #[test]
+    fn caution_when_approaching_pitch_limit() {
+        let mut tail_strike = tail_strike();
+
+        assert_eq!(tail_strike.process(&state(0.0, 8.0)), None);
+        assert_eq!(
+            tail_strike.process(&state(0.0, 9.5)),
+            Some(AlertLevel::Caution)
+        );
+        assert_eq!(
+            tail_strike.process(&state(20.0, 12.0)),
+            Some(AlertLevel::Caution)
+        );
+    }
+
+    #[test]
+    fn no_caution_above_max_height() {
+        let mut tail_strike = tail_strike();
+        assert_eq!(tail_strike.process(&state(50.0, 15.0)), None);
+    }
+}
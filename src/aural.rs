@@ -0,0 +1,423 @@
+//! Selects the voice messages for the alerts and decides when they are played
+
+use core::fmt;
+
+use crate::alerts::ALERT_STATE_SIZE;
+use crate::prelude::*;
+
+/// Voice messages, which announce the alerts to the crew
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VoiceMessage {
+    /// "PULL UP", Mode 1 and Mode 2 warning
+    PullUp,
+
+    /// "SINK RATE", Mode 1 caution
+    SinkRate,
+
+    /// "TERRAIN TERRAIN", Mode 2 caution
+    TerrainTerrain,
+
+    /// "DON'T SINK", Mode 3 caution
+    DontSink,
+
+    /// "TOO LOW TERRAIN", Mode 4 and PDA caution
+    TooLowTerrain,
+
+    /// "TOO LOW GEAR", Mode 4 caution
+    TooLowGear,
+
+    /// "TOO LOW FLAPS", Mode 4 caution
+    TooLowFlaps,
+
+    /// "GLIDESLOPE", Mode 5 caution
+    Glideslope,
+
+    /// Altitude callout of Mode 6, or "FIVE HUNDRED" of FFAC
+    Callout(Callout),
+
+    /// "CAUTION TERRAIN", FLTA caution
+    CautionTerrain,
+
+    /// "TERRAIN AHEAD PULL UP", FLTA warning
+    TerrainAheadPullUp,
+
+    /// "WINDSHEAR WINDSHEAR WINDSHEAR", Mode 7 warning
+    Windshear,
+
+    /// "CAUTION WINDSHEAR", Mode 7 caution
+    CautionWindshear,
+
+    /// "BANK ANGLE BANK ANGLE"
+    BankAngle,
+
+    /// "TAIL STRIKE"
+    TailStrike,
+}
+
+impl fmt::Display for VoiceMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            VoiceMessage::PullUp => "PULL UP",
+            VoiceMessage::SinkRate => "SINK RATE",
+            VoiceMessage::TerrainTerrain => "TERRAIN TERRAIN",
+            VoiceMessage::DontSink => "DON'T SINK",
+            VoiceMessage::TooLowTerrain => "TOO LOW TERRAIN",
+            VoiceMessage::TooLowGear => "TOO LOW GEAR",
+            VoiceMessage::TooLowFlaps => "TOO LOW FLAPS",
+            VoiceMessage::Glideslope => "GLIDESLOPE",
+            VoiceMessage::Callout(Callout::Minimums) => "MINIMUMS",
+            VoiceMessage::Callout(Callout::TwentyFiveHundred) => "TWENTY FIVE HUNDRED",
+            VoiceMessage::Callout(Callout::OneThousand) => "ONE THOUSAND",
+            VoiceMessage::Callout(Callout::FiveHundred) => "FIVE HUNDRED",
+            VoiceMessage::Callout(Callout::OneHundred) => "ONE HUNDRED",
+            VoiceMessage::Callout(Callout::Fifty) => "FIFTY",
+            VoiceMessage::Callout(Callout::Forty) => "FORTY",
+            VoiceMessage::Callout(Callout::Thirty) => "THIRTY",
+            VoiceMessage::Callout(Callout::Twenty) => "TWENTY",
+            VoiceMessage::Callout(Callout::Ten) => "TEN",
+            VoiceMessage::CautionTerrain => "CAUTION TERRAIN",
+            VoiceMessage::TerrainAheadPullUp => "TERRAIN AHEAD PULL UP",
+            VoiceMessage::Windshear => "WINDSHEAR WINDSHEAR WINDSHEAR",
+            VoiceMessage::CautionWindshear => "CAUTION WINDSHEAR",
+            VoiceMessage::BankAngle => "BANK ANGLE BANK ANGLE",
+            VoiceMessage::TailStrike => "TAIL STRIKE",
+        };
+        f.write_str(text)
+    }
+}
+
+impl VoiceMessage {
+    /// The voice message which announces an alert, `None` if the alert is not announced aurally
+    pub fn for_alert(
+        alert: Alert,
+        level: AlertLevel,
+        sub_type: Option<AlertSubType>,
+    ) -> Option<VoiceMessage> {
+        use AlertLevel::*;
+
+        let message = match (alert, level, sub_type) {
+            (Alert::Mode1 | Alert::Mode2, Warning, _) => VoiceMessage::PullUp,
+            (Alert::Mode1, Caution, _) => VoiceMessage::SinkRate,
+            (Alert::Mode2, Caution, _) => VoiceMessage::TerrainTerrain,
+            (Alert::Mode3, Caution, _) => VoiceMessage::DontSink,
+            (Alert::Mode4, Caution, Some(AlertSubType::TooLowGear)) => VoiceMessage::TooLowGear,
+            (Alert::Mode4, Caution, Some(AlertSubType::TooLowFlaps)) => VoiceMessage::TooLowFlaps,
+            (Alert::Mode4 | Alert::Pda, Caution, _) => VoiceMessage::TooLowTerrain,
+            (Alert::Mode5, Caution, _) => VoiceMessage::Glideslope,
+            (Alert::Mode6, _, Some(AlertSubType::Callout(callout))) => {
+                VoiceMessage::Callout(callout)
+            }
+            (Alert::Ffac, _, _) => VoiceMessage::Callout(Callout::FiveHundred),
+            (Alert::Flta, Warning, _) => VoiceMessage::TerrainAheadPullUp,
+            (Alert::Flta, Caution, _) => VoiceMessage::CautionTerrain,
+            (Alert::Mode7, Warning, _) => VoiceMessage::Windshear,
+            (Alert::Mode7, Caution, _) => VoiceMessage::CautionWindshear,
+            (Alert::BankAngle, _, _) => VoiceMessage::BankAngle,
+            (Alert::TailStrike, _, _) => VoiceMessage::TailStrike,
+            _ => return None,
+        };
+        Some(message)
+    }
+
+    /// Time it takes to play the voice message
+    pub fn duration(&self) -> Time {
+        let seconds = match self {
+            VoiceMessage::PullUp | VoiceMessage::SinkRate | VoiceMessage::DontSink => 1.0,
+            VoiceMessage::Glideslope | VoiceMessage::TailStrike => 1.0,
+            VoiceMessage::Callout(Callout::TwentyFiveHundred) => 1.5,
+            VoiceMessage::Callout(_) => 1.0,
+            VoiceMessage::TerrainTerrain | VoiceMessage::CautionTerrain => 1.5,
+            VoiceMessage::TooLowTerrain | VoiceMessage::TooLowGear | VoiceMessage::TooLowFlaps => {
+                1.5
+            }
+            VoiceMessage::CautionWindshear => 1.5,
+            VoiceMessage::TerrainAheadPullUp | VoiceMessage::BankAngle => 2.0,
+            VoiceMessage::Windshear => 2.5,
+        };
+        Time::new::<second>(seconds)
+    }
+
+    /// Time from the start of the voice message after which it is repeated while its alert is
+    /// present, `None` if it is played only once per alert
+    pub fn repeat_interval(&self) -> Option<Time> {
+        let seconds = match self {
+            // warnings to pull up are repeated without a pause
+            VoiceMessage::PullUp | VoiceMessage::TerrainAheadPullUp => {
+                return Some(self.duration())
+            }
+            VoiceMessage::CautionTerrain => 7.0,
+            VoiceMessage::Callout(_) | VoiceMessage::Windshear | VoiceMessage::CautionWindshear => {
+                return None
+            }
+            _ => 3.0,
+        };
+        Some(Time::new::<second>(seconds))
+    }
+}
+
+/// An alert which is announced by a voice message
+#[derive(Clone, Copy, Debug)]
+struct Announcement {
+    alert: Alert,
+    level: AlertLevel,
+    message: VoiceMessage,
+    priority: u8,
+
+    /// Time stamp at which the voice message was last started
+    last_started: Option<Time>,
+}
+
+impl Announcement {
+    fn is_due(&self, timestamp: Time) -> bool {
+        match (self.last_started, self.message.repeat_interval()) {
+            (None, _) => true,
+            (Some(last_started), Some(interval)) => timestamp - last_started >= interval,
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// Decides which voice message is played, based on the time stamps of the `AircraftState`s
+///
+/// A voice message is completed before the next one starts, unless a warning of higher priority
+/// interrupts it. Of all voice messages waiting to be played, the one of the alert with the
+/// highest priority is played first. Voice messages of alerts suppressed by an external alert of
+/// higher priority are deferred until the external alert ends.
+#[derive(Debug, Default)]
+pub(crate) struct AuralScheduler {
+    /// The voice message which is played, with its priority and the time stamp at which it started
+    playing: Option<(VoiceMessage, u8, Time)>,
+
+    /// The present alerts which are announced by a voice message
+    announcements: [Option<Announcement>; ALERT_STATE_SIZE],
+
+    /// The voice message which was cut off by the last update
+    silenced: Option<VoiceMessage>,
+
+    last_timestamp: Option<Time>,
+}
+
+impl AuralScheduler {
+    /// Processes the present alerts, returns the voice message to start at this time stamp
+    ///
+    /// The returned voice message replaces the one which is still played, if any.
+    pub fn update(&mut self, timestamp: Time, alert_state: &AlertState) -> Option<VoiceMessage> {
+        // a time stamp from the past means that the time was reset, forget about the old one
+        if matches!(self.last_timestamp.replace(timestamp), Some(last) if last > timestamp) {
+            *self = Self {
+                last_timestamp: Some(timestamp),
+                ..Default::default()
+            };
+        }
+
+        self.silenced = None;
+
+        let mut announcements = [None; ALERT_STATE_SIZE];
+        for (option, (alert, level)) in announcements.iter_mut().zip(alert_state.iter()) {
+            let sub_type = alert_state.sub_type(alert);
+            let message = match VoiceMessage::for_alert(alert, level, sub_type) {
+                Some(message) => message,
+                None => continue,
+            };
+
+            // a voice message which is still announcing the same alert keeps its history
+            let last_started = self
+                .announcements
+                .iter()
+                .flatten()
+                .find(|e| e.alert == alert && e.message == message)
+                .and_then(|e| e.last_started);
+
+            *option = Some(Announcement {
+                alert,
+                level,
+                message,
+                priority: alert_state.priority(alert, level, sub_type),
+                last_started,
+            });
+        }
+        self.announcements = announcements;
+
+        if let Some((message, _, started)) = self.playing {
+            if timestamp - started >= message.duration() {
+                self.playing = None;
+            }
+        }
+
+        // the TAWS must not talk over an external alert of higher priority, e.g. a TCAS RA
+        if let (Some((message, priority, _)), Some(external_priority)) =
+            (self.playing, alert_state.external_priority())
+        {
+            if priority > external_priority {
+                self.silenced = Some(message);
+                self.playing = None;
+            }
+        }
+
+        let external_priority = alert_state.external_priority().unwrap_or(u8::MAX);
+        let next = self
+            .announcements
+            .iter_mut()
+            .flatten()
+            .filter(|announcement| announcement.priority <= external_priority)
+            .filter(|announcement| announcement.is_due(timestamp))
+            .min_by_key(|announcement| announcement.priority)?;
+
+        let start = match self.playing {
+            None => true,
+            Some((_, playing_priority, _)) => {
+                next.level == AlertLevel::Warning && next.priority < playing_priority
+            }
+        };
+        if !start {
+            return None;
+        }
+
+        next.last_started = Some(timestamp);
+        self.playing = Some((next.message, next.priority, timestamp));
+        Some(next.message)
+    }
+
+    /// The voice message which must be stopped, as an external alert of higher priority became
+    /// active while it was played
+    pub fn silenced(&self) -> Option<VoiceMessage> {
+        self.silenced
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::string::ToString;
+    use std::vec::Vec;
+
+    fn alert_state(alerts: &[(Alert, AlertLevel)]) -> AlertState {
+        let mut alert_state = AlertState::default();
+        for (alert, level) in alerts {
+            alert_state.insert(*alert, *level, None);
+        }
+        alert_state
+    }
+
+    /// Updates the scheduler every tenth of a second from `from` to `to` seconds, returning the
+    /// started voice messages with their time stamp
+    fn run(
+        scheduler: &mut AuralScheduler,
+        from: f64,
+        to: f64,
+        alerts: &[(Alert, AlertLevel)],
+    ) -> Vec<(f64, VoiceMessage)> {
+        run_state(scheduler, from, to, &alert_state(alerts))
+    }
+
+    fn run_state(
+        scheduler: &mut AuralScheduler,
+        from: f64,
+        to: f64,
+        alert_state: &AlertState,
+    ) -> Vec<(f64, VoiceMessage)> {
+        let mut started = Vec::new();
+        let tenths = (from * 10.0).round() as i32..(to * 10.0).round() as i32;
+        for time in tenths.map(|tenth| f64::from(tenth) / 10.0) {
+            if let Some(message) = scheduler.update(Time::new::<second>(time), alert_state) {
+                started.push((time, message));
+            }
+        }
+        started
+    }
+
+    const SINK_RATE: (Alert, AlertLevel) = (Alert::Mode1, AlertLevel::Caution);
+    const PULL_UP: (Alert, AlertLevel) = (Alert::Mode1, AlertLevel::Warning);
+    const DONT_SINK: (Alert, AlertLevel) = (Alert::Mode3, AlertLevel::Caution);
+
+    #[test]
+    fn messages_of_the_alerts() {
+        assert_eq!(
+            VoiceMessage::for_alert(
+                Alert::Mode4,
+                AlertLevel::Caution,
+                Some(AlertSubType::TooLowGear)
+            ),
+            Some(VoiceMessage::TooLowGear)
+        );
+        assert_eq!(
+            VoiceMessage::for_alert(Alert::Ffac, AlertLevel::Annunciation, None),
+            Some(VoiceMessage::Callout(Callout::FiveHundred))
+        );
+        assert_eq!(VoiceMessage::PullUp.to_string(), "PULL UP");
+    }
+
+    #[test]
+    fn repeated_while_present() {
+        let mut scheduler = AuralScheduler::default();
+
+        assert_eq!(
+            run(&mut scheduler, 0.0, 7.0, &[SINK_RATE]),
+            [
+                (0.0, VoiceMessage::SinkRate),
+                (3.0, VoiceMessage::SinkRate),
+                (6.0, VoiceMessage::SinkRate)
+            ]
+        );
+    }
+
+    #[test]
+    fn lower_priority_waits_for_completion() {
+        let mut scheduler = AuralScheduler::default();
+
+        run(&mut scheduler, 0.0, 0.5, &[DONT_SINK]);
+        assert_eq!(
+            run(&mut scheduler, 0.5, 1.5, &[DONT_SINK, SINK_RATE]),
+            [(1.0, VoiceMessage::SinkRate)]
+        );
+    }
+
+    #[test]
+    fn warning_interrupts() {
+        let mut scheduler = AuralScheduler::default();
+
+        run(&mut scheduler, 0.0, 0.5, &[DONT_SINK]);
+        assert_eq!(
+            run(&mut scheduler, 0.5, 1.0, &[DONT_SINK, PULL_UP]),
+            [(0.5, VoiceMessage::PullUp)]
+        );
+    }
+
+    #[test]
+    fn played_again_after_a_new_onset() {
+        let mut scheduler = AuralScheduler::default();
+        let callout = (Alert::Ffac, AlertLevel::Annunciation);
+
+        assert_eq!(
+            run(&mut scheduler, 0.0, 5.0, &[callout]),
+            [(0.0, VoiceMessage::Callout(Callout::FiveHundred))]
+        );
+        run(&mut scheduler, 5.0, 6.0, &[]);
+        assert_eq!(
+            run(&mut scheduler, 6.0, 7.0, &[callout]),
+            [(6.0, VoiceMessage::Callout(Callout::FiveHundred))]
+        );
+    }
+
+    #[test]
+    fn external_alerts_defer_and_silence() {
+        let mut scheduler = AuralScheduler::default();
+        let mut alert_state = alert_state(&[DONT_SINK]);
+
+        run_state(&mut scheduler, 0.0, 0.5, &alert_state);
+        alert_state.set_external_alerts(&[ExternalAlert::PredictiveWindshearWarning]);
+        assert_eq!(
+            scheduler.update(Time::new::<second>(0.5), &alert_state),
+            None
+        );
+        assert_eq!(scheduler.silenced(), Some(VoiceMessage::DontSink));
+        assert_eq!(run_state(&mut scheduler, 0.6, 5.0, &alert_state), []);
+
+        alert_state.set_external_alerts(&[]);
+        assert_eq!(
+            run_state(&mut scheduler, 5.0, 5.5, &alert_state),
+            [(5.0, VoiceMessage::DontSink)]
+        );
+    }
+}
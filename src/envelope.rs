@@ -96,6 +96,23 @@ impl<const N: usize> Envelope<N> {
             + self.derivatives[interval_index] * (x - self.points[interval_index].0);
         y <= fx
     }
+
+    /// Checks that the points are still ordered and that the slopes still match them, which
+    /// guards against corrupted memory
+    pub fn is_consistent(&self) -> bool {
+        self.points
+            .windows(2)
+            .zip(self.derivatives.iter())
+            .all(|(section, derivative)| {
+                let ((x, y), (x_, y_)) = (section[0], section[1]);
+                x < x_ && (y_ - y) / (x_ - x) == *derivative
+            })
+    }
+
+    /// Checks whether all points of another envelope are in this envelope
+    pub fn encloses<const M: usize>(&self, other: &Envelope<M>) -> bool {
+        other.points.iter().all(|(x, y)| self.contains(*x, *y))
+    }
 }
 
 #[cfg(test)]
@@ -107,6 +124,24 @@ mod test {
         Envelope::new(points).unwrap()
     }
 
+    #[test]
+    fn consistent_envelope() {
+        let mut evp = init_envelope();
+        assert!(evp.is_consistent());
+
+        evp.derivatives[1] += 1.0;
+        assert!(!evp.is_consistent());
+    }
+
+    #[test]
+    fn enclosed_envelope() {
+        let evp = init_envelope();
+        let inner = Envelope::new([(1700, 150), (10100, 1000), (10101, 1000)]).unwrap();
+
+        assert!(evp.encloses(&inner));
+        assert!(!inner.encloses(&evp));
+    }
+
     #[test]
     fn left_lower_bound() {
         let evp = init_envelope();
@@ -133,6 +168,7 @@ mod test {
 
     #[test]
     #[should_panic(expected = "called `Option::unwrap()` on a `None` value")]
+    #[allow(unused_variables)]
     fn input_list_too_small() {
         let evp = Envelope::<1>::new([(0, 0)]).unwrap();
     }
@@ -0,0 +1,292 @@
+use core::fmt;
+
+use crate::prelude::*;
+
+/// Phase of the flight, as detected by the TAWS from the history of `AircraftState`s
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FlightPhase {
+    /// On ground, either parked or taxiing
+    Ground,
+
+    /// Take-off roll and initial climb, until the take-off height is reached
+    Takeoff,
+
+    /// Climbing after the take-off or go around
+    Climb,
+
+    /// Neither climbing nor approaching a runway, this includes the descent from cruise altitude
+    Cruise,
+
+    /// Descending close to the terrain, or descending in landing configuration after a low circuit
+    Approach,
+
+    /// Climbing away after an approach, until the take-off height is reached
+    GoAround,
+
+    /// Flare, touch down and rollout
+    Landing,
+}
+
+impl fmt::Display for FlightPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FlightPhase::Ground => "ground",
+            FlightPhase::Takeoff => "take-off",
+            FlightPhase::Climb => "climb",
+            FlightPhase::Cruise => "cruise",
+            FlightPhase::Approach => "approach",
+            FlightPhase::GoAround => "go around",
+            FlightPhase::Landing => "landing",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Derives the `FlightPhase` from successive `AircraftState`s
+#[derive(Debug, Default)]
+pub(crate) struct FlightPhaseDetector {
+    phase: Option<FlightPhase>,
+
+    /// Timestamp of the first `AircraftState` of the ongoing climb, `None` if not climbing
+    climb_start: Option<Time>,
+}
+
+impl FlightPhaseDetector {
+    /// Height above terrain below which the aircraft is considered to be on ground, if weight on
+    /// wheels is not available
+    const GROUND_HEIGHT: f64 = 5.0;
+
+    /// Ground speed in knots above which the aircraft is considered to be rolling for take-off or
+    /// landing rather than taxiing
+    const TAXI_SPEED: f64 = 40.0;
+
+    /// Climb rate in feet per minute beyond which the aircraft is considered to be climbing or
+    /// descending rather than flying level
+    const LEVEL_RATE: f64 = 300.0;
+
+    /// Height above terrain which ends the take-off and go around phases
+    const TAKEOFF_HEIGHT: f64 = 1000.0;
+
+    /// Height above terrain below which a descent is considered to be an approach
+    const APPROACH_HEIGHT: f64 = 2000.0;
+
+    /// Height above terrain below which an approach turns into the landing
+    const LANDING_HEIGHT: f64 = 50.0;
+
+    /// Duration in seconds of a climb which turns an approach into a go around, so that a brief
+    /// climb in turbulence or when capturing the glideslope from below does not
+    const GO_AROUND_TIME: f64 = 5.0;
+
+    /// The current flight phase, `Ground` if no `AircraftState` was processed yet
+    pub fn phase(&self) -> FlightPhase {
+        self.phase.unwrap_or(FlightPhase::Ground)
+    }
+
+    /// Processes a new `AircraftState`, returns the new flight phase if it changed
+    pub fn update(&mut self, state: &AircraftState) -> Option<FlightPhase> {
+        let climbing = state.climb_rate.get::<foot_per_minute>() > Self::LEVEL_RATE;
+        self.climb_start = match self.climb_start {
+            // a timestamp from before the start restarts the climb, e.g. after a reset of the clock
+            Some(start) if climbing && start <= state.timestamp => Some(start),
+            _ if climbing => Some(state.timestamp),
+            _ => None,
+        };
+        let sustained_climb = matches!(
+            self.climb_start,
+            Some(start) if state.timestamp - start >= Time::new::<second>(Self::GO_AROUND_TIME)
+        );
+
+        let phase = Self::next(self.phase, state, sustained_climb);
+        (self.phase.replace(phase) != Some(phase)).then_some(phase)
+    }
+
+    fn next(
+        last: Option<FlightPhase>,
+        state: &AircraftState,
+        sustained_climb: bool,
+    ) -> FlightPhase {
+        use FlightPhase::*;
+
+        let height = state.altitude_ground.get::<foot>();
+        let climb_rate = state.climb_rate.get::<foot_per_minute>();
+        let on_ground = match state.weight_on_wheels {
+            Discrete::Active => true,
+            Discrete::Inactive => false,
+            Discrete::NotAvailable => height < Self::GROUND_HEIGHT,
+        };
+        let rolling = state.speed_ground.get::<knot>() > Self::TAXI_SPEED;
+        let climbing = climb_rate > Self::LEVEL_RATE;
+        let descending = climb_rate < -Self::LEVEL_RATE;
+        let approaching = height < Self::APPROACH_HEIGHT
+            && (descending || state.landing_gear == GearPosition::Down);
+        let landing_configuration =
+            state.landing_gear == GearPosition::Down && state.flaps == FlapPosition::Landing;
+
+        // the phase in which an airborne aircraft is found when nothing else is known
+        let en_route = if approaching {
+            Approach
+        } else if climbing {
+            Climb
+        } else {
+            Cruise
+        };
+
+        match last {
+            None if on_ground => Ground,
+            None => en_route,
+
+            Some(Ground) | Some(Takeoff) if on_ground => {
+                if rolling {
+                    Takeoff
+                } else {
+                    Ground
+                }
+            }
+            Some(Landing) if on_ground => {
+                if rolling {
+                    Landing
+                } else {
+                    Ground
+                }
+            }
+            Some(_) if on_ground => Landing,
+
+            // a circuit which stays below the take-off height ends with the descent to land
+            Some(Takeoff) | Some(GoAround) if descending && landing_configuration => Approach,
+            Some(Ground) | Some(Takeoff) if height < Self::TAKEOFF_HEIGHT => Takeoff,
+            Some(GoAround) if height < Self::TAKEOFF_HEIGHT => GoAround,
+            Some(Approach) | Some(Landing) if sustained_climb => GoAround,
+            Some(Approach) | Some(Landing) if height < Self::LANDING_HEIGHT => Landing,
+            Some(Landing) => Approach,
+            Some(Approach) if height < Self::APPROACH_HEIGHT => Approach,
+            Some(_) => en_route,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn state(height: f64, climb_rate: f64, speed: f64) -> AircraftState {
+        state_at(0.0, height, climb_rate, speed)
+    }
+
+    fn state_at(time: f64, height: f64, climb_rate: f64, speed: f64) -> AircraftState {
+        AircraftState {
+            timestamp: Time::new::<second>(time),
+            altitude: Length::new::<foot>(height),
+            altitude_ground: Length::new::<foot>(height),
+            climb_rate: Velocity::new::<foot_per_minute>(climb_rate),
+            speed_ground: Velocity::new::<knot>(speed),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn airborne_at_power_up() {
+        let mut detector = FlightPhaseDetector::default();
+        assert_eq!(detector.phase(), FlightPhase::Ground);
+
+        assert_eq!(
+            detector.update(&state(5000.0, 0.0, 250.0)),
+            Some(FlightPhase::Cruise)
+        );
+        assert_eq!(detector.update(&state(5000.0, 0.0, 250.0)), None);
+    }
+
+    #[test]
+    fn rejected_takeoff() {
+        let mut detector = FlightPhaseDetector::default();
+
+        detector.update(&state(0.0, 0.0, 10.0));
+        assert_eq!(
+            detector.update(&state(0.0, 0.0, 100.0)),
+            Some(FlightPhase::Takeoff)
+        );
+        assert_eq!(
+            detector.update(&state(0.0, 0.0, 20.0)),
+            Some(FlightPhase::Ground)
+        );
+    }
+
+    #[test]
+    fn touch_and_go() {
+        let mut detector = FlightPhaseDetector::default();
+
+        detector.update(&state(1000.0, -700.0, 140.0));
+        assert_eq!(detector.phase(), FlightPhase::Approach);
+        assert_eq!(
+            detector.update(&state(0.0, 0.0, 120.0)),
+            Some(FlightPhase::Landing)
+        );
+        assert_eq!(detector.update(&state_at(1.0, 10.0, 800.0, 130.0)), None);
+        assert_eq!(
+            detector.update(&state_at(6.0, 80.0, 800.0, 130.0)),
+            Some(FlightPhase::GoAround)
+        );
+        assert_eq!(
+            detector.update(&state_at(60.0, 1200.0, 800.0, 150.0)),
+            Some(FlightPhase::Climb)
+        );
+    }
+
+    #[test]
+    fn brief_climb_on_approach() {
+        let mut detector = FlightPhaseDetector::default();
+
+        detector.update(&state_at(0.0, 800.0, -700.0, 140.0));
+        assert_eq!(detector.phase(), FlightPhase::Approach);
+
+        // a gust lifts the aircraft for some seconds, e.g. while capturing the glideslope
+        for time in 1..5 {
+            assert_eq!(
+                detector.update(&state_at(f64::from(time), 800.0, 600.0, 140.0)),
+                None
+            );
+        }
+        assert_eq!(detector.update(&state_at(5.0, 800.0, -700.0, 140.0)), None);
+        assert_eq!(detector.phase(), FlightPhase::Approach);
+    }
+
+    #[test]
+    fn low_circuit() {
+        let mut detector = FlightPhaseDetector::default();
+
+        detector.update(&state(0.0, 0.0, 10.0));
+        assert_eq!(
+            detector.update(&state(0.0, 0.0, 120.0)),
+            Some(FlightPhase::Takeoff)
+        );
+        assert_eq!(detector.update(&state(800.0, 0.0, 120.0)), None);
+
+        // descending on the base leg before the landing configuration is set
+        assert_eq!(detector.update(&state(700.0, -700.0, 120.0)), None);
+        assert_eq!(
+            detector.update(&AircraftState {
+                landing_gear: GearPosition::Down,
+                flaps: FlapPosition::Landing,
+                ..state(600.0, -700.0, 120.0)
+            }),
+            Some(FlightPhase::Approach)
+        );
+    }
+
+    #[test]
+    fn level_off_and_descent() {
+        let mut detector = FlightPhaseDetector::default();
+
+        detector.update(&state(3000.0, 1500.0, 200.0));
+        assert_eq!(detector.phase(), FlightPhase::Climb);
+        assert_eq!(
+            detector.update(&state(9000.0, 0.0, 250.0)),
+            Some(FlightPhase::Cruise)
+        );
+        assert_eq!(detector.update(&state(4000.0, -1500.0, 250.0)), None);
+        assert_eq!(
+            detector.update(&state(1900.0, -1500.0, 200.0)),
+            Some(FlightPhase::Approach)
+        );
+    }
+}
@@ -11,38 +11,69 @@
 #![no_std]
 #![deny(unsafe_code)]
 
-pub use alerts::{functionalities, Alert, AlertLevel, AlertState};
+#[cfg(any(test, feature = "std"))]
+extern crate std;
+
+pub use alert_manager::{AlertEvent, AlertEvents, AlertTiming};
+#[allow(deprecated)]
+pub use alerts::priority;
+pub use alerts::{functionalities, Alert, AlertLevel, AlertState, AlertSubType, Callout};
+pub use aural::VoiceMessage;
+pub use flight_phase::FlightPhase;
 use prelude::*;
+pub use priorities::{AlertPriorities, ExternalAlert, PrioritizedAlert};
+pub use self_test::{SelfTestFailure, SelfTestFailures, SelfTestReport, SelfTestStep};
+pub use signal_test::{InputFaults, UnplausibleSignal};
+pub use taws_state::{FunctionStatus, TawsState};
 pub use types::*;
 
 #[macro_use]
 mod macros;
 
+mod alert_manager;
 mod alerts;
+mod aural;
 mod envelope;
+mod flight_phase;
 pub mod prelude;
+mod priorities;
+mod self_test;
+mod signal_test;
+mod taws_state;
+pub mod terrain_server;
 mod types;
 
 /// Represents one instance of a TAWS
 #[derive(Debug)]
-pub struct Taws {
+pub struct Taws<'a> {
     /// `true` if the TAWS is armed
     ///
     /// There is no specific condition for changing this to `false`.
     pub armed: bool,
-    config: TawsConfig,
-    ffac: functionalities::Ffac,
-    flta: functionalities::Flta,
+    config: TawsConfig<'a>,
+    alert_manager: alert_manager::AlertManager,
+    aural: aural::AuralScheduler,
+    flight_phase: flight_phase::FlightPhaseDetector,
+    self_test: self_test::SelfTest,
+    signal_test: signal_test::SignalTest,
+    bankangle: functionalities::BankAngle,
+    ffac: functionalities::Ffac<'a>,
+    flta: functionalities::Flta<'a>,
     mode1: functionalities::Mode1,
     mode2: functionalities::Mode2,
     mode3: functionalities::Mode3,
     mode4: functionalities::Mode4,
     mode5: functionalities::Mode5,
-    pda: functionalities::Pda,
+    mode6: functionalities::Mode6<'a>,
+    mode7: functionalities::Mode7,
+    pda: functionalities::Pda<'a>,
+    tailstrike: functionalities::TailStrike,
 }
 
-impl Taws {
-    functionalities![Ffac, Flta, Mode1, Mode2, Mode3, Mode4, Mode5, Pda];
+impl<'a> Taws<'a> {
+    functionalities![
+        BankAngle, Ffac, Flta, Mode1, Mode2, Mode3, Mode4, Mode5, Mode6, Mode7, Pda, TailStrike
+    ];
 
     /// Create a new instance of `Taws`
     ///  
@@ -50,17 +81,45 @@ impl Taws {
     ///
     /// * `config` - The configuration which this TAWS instance shall use
     ///
+    /// The terrain and runway sources of the configuration are handed to every alert system.
+    ///
     /// # Example
     ///
     /// ```
     /// use opentaws::prelude::*;
     ///
-    /// let config = TawsConfig::default();
-    /// let taws = Taws::new(config);
+    /// #[derive(Debug)]
+    /// struct SeaLevel;
+    ///
+    /// impl TerrainServer for SeaLevel {
+    ///     fn elevation(&self, _position: &Position) -> Result<Length, TerrainError> {
+    ///         Ok(Length::new::<foot>(0.0))
+    ///     }
+    /// }
+    ///
+    /// let config = TawsConfig {
+    ///     terrain_server: Some(&SeaLevel),
+    ///     ..Default::default()
+    /// };
+    /// let taws = Taws::new(config).unwrap();
     /// ```
-    pub fn new(config: TawsConfig) -> Self {
+    ///
+    /// # Errors
+    ///
+    /// `ConfigError::Unprioritized` if `config.alert_priorities` misses an alert which the alert
+    /// systems may emit, see `AlertPriorities::unprioritized`.
+    pub fn new(config: TawsConfig<'a>) -> Result<Self, ConfigError> {
         use alerts::*;
 
+        if let Some((alert, level, sub_type)) = config.alert_priorities.unprioritized().next() {
+            return Err(ConfigError::Unprioritized {
+                alert,
+                level,
+                sub_type,
+            });
+        }
+
+        let bankangle = functionalities::BankAngle::new(&config);
         let ffac = functionalities::Ffac::new(&config);
         let flta = functionalities::Flta::new(&config);
         let mode1 = functionalities::Mode1::new(&config);
@@ -68,11 +127,20 @@ impl Taws {
         let mode3 = functionalities::Mode3::new(&config);
         let mode4 = functionalities::Mode4::new(&config);
         let mode5 = functionalities::Mode5::new(&config);
+        let mode6 = functionalities::Mode6::new(&config);
+        let mode7 = functionalities::Mode7::new(&config);
         let pda = functionalities::Pda::new(&config);
+        let tailstrike = functionalities::TailStrike::new(&config);
 
-        Self {
+        Ok(Self {
             armed: true,
             config,
+            alert_manager: Default::default(),
+            aural: Default::default(),
+            flight_phase: Default::default(),
+            self_test: Default::default(),
+            signal_test: Default::default(),
+            bankangle,
             ffac,
             flta,
             mode1,
@@ -80,12 +148,18 @@ impl Taws {
             mode3,
             mode4,
             mode5,
+            mode6,
+            mode7,
             pda,
-        }
+            tailstrike,
+        })
     }
 
     /// Returns `true` if the alert system is armed
     ///
+    /// An alert system affected by a self-test failure is disarmed until the failure is gone. Then
+    /// its own armed state applies again, including an `arm` or `disarm` in the meantime.
+    ///
     /// # Arguments
     ///
     /// * `alert_system` - The alert system whiches armed state shall be checked
@@ -95,13 +169,14 @@ impl Taws {
     /// ```
     /// # use opentaws::prelude::*;
     /// # let config = TawsConfig::default();
-    /// # let taws = Taws::new(config);
+    /// # let taws = Taws::new(config).unwrap();
     /// if taws.is_armed(Alert::Mode1) {
     ///     // ...
     /// }
     /// ```
     pub fn is_armed(&self, alert_system: Alert) -> bool {
         self.get_functionality(alert_system).is_armed()
+            && !self.self_test.failures().affect(alert_system)
     }
 
     /// Arms a specific alert system
@@ -115,7 +190,7 @@ impl Taws {
     /// ```
     /// # use opentaws::prelude::*;
     /// # let config = TawsConfig::default();
-    /// # let mut taws = Taws::new(config);
+    /// # let mut taws = Taws::new(config).unwrap();
     /// taws.arm(Alert::Mode1);
     ///
     /// assert!(taws.is_armed(Alert::Mode1));
@@ -135,7 +210,7 @@ impl Taws {
     /// ```
     /// # use opentaws::prelude::*;
     /// # let config = TawsConfig::default();
-    /// # let mut taws = Taws::new(config);
+    /// # let mut taws = Taws::new(config).unwrap();
     /// taws.disarm(Alert::Mode1);
     ///
     /// assert_eq!(taws.is_armed(Alert::Mode1), false);
@@ -155,7 +230,7 @@ impl Taws {
     /// ```
     /// # use opentaws::prelude::*;
     /// # let config = TawsConfig::default();
-    /// # let taws = Taws::new(config);
+    /// # let taws = Taws::new(config).unwrap();
     /// if taws.is_inhibited(Alert::Mode1) {
     ///     // ...
     /// }
@@ -175,7 +250,7 @@ impl Taws {
     /// ```
     /// # use opentaws::prelude::*;
     /// # let config = TawsConfig::default();
-    /// # let mut taws = Taws::new(config);
+    /// # let mut taws = Taws::new(config).unwrap();
     /// taws.inhibit(Alert::Mode1);
     ///
     /// assert!(taws.is_inhibited(Alert::Mode1));
@@ -195,7 +270,7 @@ impl Taws {
     /// ```
     /// # use opentaws::prelude::*;
     /// # let config = TawsConfig::default();
-    /// # let mut taws = Taws::new(config);
+    /// # let mut taws = Taws::new(config).unwrap();
     /// taws.uninhibit(Alert::Mode1);
     ///
     /// assert_eq!(taws.is_inhibited(Alert::Mode1), false);
@@ -204,6 +279,63 @@ impl Taws {
         self.get_mut_functionality(alert_system).uninhibit()
     }
 
+    /// Cancel the glideslope alerting (Mode 5) until the next approach
+    ///
+    /// This is the pilot activated glideslope cancel. It is reset automatically once the aircraft
+    /// climbs above 2000 foot or descends below 30 foot above terrain.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use opentaws::prelude::*;
+    /// # let config = TawsConfig::default();
+    /// # let mut taws = Taws::new(config).unwrap();
+    /// taws.glideslope_cancel();
+    /// ```
+    pub fn glideslope_cancel(&mut self) {
+        self.mode5.cancel()
+    }
+
+    /// Returns the current flight phase
+    ///
+    /// The flight phase is derived from the aircraft states processed so far. Before the first
+    /// aircraft state is processed, the aircraft is assumed to be on ground.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use opentaws::prelude::*;
+    /// # let config = TawsConfig::default();
+    /// # let taws = Taws::new(config).unwrap();
+    /// assert_eq!(taws.flight_phase(), FlightPhase::Ground);
+    /// ```
+    pub fn flight_phase(&self) -> FlightPhase {
+        self.flight_phase.phase()
+    }
+
+    /// Run the initiated self-test
+    ///
+    /// Steps through the aural alerting, the visual alerting, the altitude callouts, the terrain
+    /// display outputs and the fault reporting. The alert systems affected by a failed step are
+    /// disarmed until an initiated self-test passes the step again. Failed steps are also reported
+    /// by every following `process`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use opentaws::prelude::*;
+    /// # let config = TawsConfig::default();
+    /// # let mut taws = Taws::new(config).unwrap();
+    /// let report = taws.self_test();
+    ///
+    /// for step in report.failures() {
+    ///     println!("self-test failed: {}", step);
+    /// }
+    /// ```
+    pub fn self_test(&mut self) -> SelfTestReport {
+        self.self_test.initiate(&self.config, &self.signal_test)
+    }
+
     /// Process a new aircraft state
     ///
     /// This method must be called regularly for the TAWS to function properly!
@@ -211,33 +343,123 @@ impl Taws {
     ///
     /// # Arguments
     ///
-    /// * `alert_system` - The alert system which shall be uninhibited
+    /// * `state` - The current state of the aircraft
+    /// * `external_alerts` - The alerts of other systems, e.g. TCAS, which are active. TAWS alerts
+    ///   of lower priority, according to `TawsConfig::alert_priorities` or the priority carried by
+    ///   an `ExternalAlert::Other`, are suppressed.
     ///
     /// # Example
     ///
     /// ```
     /// # use opentaws::prelude::*;
     /// # let config = TawsConfig::default();
-    /// # let mut taws = Taws::new(config);
+    /// # let mut taws = Taws::new(config).unwrap();
     /// let aicraft_state = AircraftState::default();
     ///
-    /// let alert_state = taws.process(&aicraft_state);
-    /// println!("Received AlertState: {:?}", alert_state);
+    /// let taws_state = taws.process(&aicraft_state, &[ExternalAlert::TcasTrafficAdvisory]);
+    /// println!("Received AlertState: {:?}", taws_state.alert_state());
+    ///
+    /// if taws_state.terrain_failed() {
+    ///     println!("TERR FAIL");
+    /// }
     /// ```
-    pub fn process(&mut self, state: &AircraftState) -> AlertState {
-        let mut alert_state = alerts::AlertState::default();
-
-        for (alert, alert_system) in self
-            .functionality_mut_array()
-            .iter_mut()
-            .filter(|(_, alert_system)| !alert_system.is_inhibited())
-        {
-            if let Some(alert_level) = alert_system.process(state) {
-                alert_state.insert(*alert, alert_level);
+    pub fn process(
+        &mut self,
+        state: &AircraftState,
+        external_alerts: &[ExternalAlert],
+    ) -> TawsState {
+        // the alert systems expect angles within their usual ranges, e.g. a roll of -10° not 350°
+        let mut state = state.clone();
+        state.normalize();
+        let state = &state;
+
+        let input_faults = if self.config.signal_test {
+            self.signal_test.check(state)
+        } else {
+            Default::default()
+        };
+
+        if self.config.continuous_self_test {
+            let mut integrity_failures = SelfTestFailures::default();
+            for (alert, alert_system) in self.functionality_mut_array().iter() {
+                if !alert_system.check_integrity() {
+                    integrity_failures.insert(SelfTestFailure::Integrity(*alert));
+                }
             }
+            self.self_test
+                .monitor(state, self.config.terrain_server, integrity_failures);
+        }
+        let self_test_failures = self.self_test.failures();
+        let mut taws_state = TawsState::new(
+            self.config.alert_priorities,
+            input_faults,
+            self_test_failures,
+        );
+
+        // the flight phase relies on most of the signals, so it holds on any fault
+        if input_faults.is_empty() {
+            if let Some(phase) = self.flight_phase.update(state) {
+                for (_, alert_system) in self.functionality_mut_array().iter_mut() {
+                    alert_system.flight_phase_changed(phase);
+                }
+            }
+        }
+
+        for (alert, alert_system) in self.functionality_mut_array().iter_mut() {
+            // implausible input data never reaches the alert systems relying on it
+            let skipped = input_faults.affect(*alert);
+
+            // a failed alert system keeps track of the flight, but it is disarmed by the failure
+            let failed = self_test_failures.affect(*alert);
+            let armed = alert_system.is_armed() && !failed;
+
+            if !skipped && !alert_system.is_inhibited() {
+                let alert_level = alert_system.process(state).filter(|_| !failed);
+                if let Some(alert_level) = alert_level {
+                    debug_assert!(alert
+                        .outputs()
+                        .contains(&(alert_level, alert_system.sub_type())));
+                    taws_state.alert_state_mut().insert(
+                        *alert,
+                        alert_level,
+                        alert_system.sub_type(),
+                    );
+                }
+            }
+
+            taws_state.insert_function(
+                *alert,
+                FunctionStatus {
+                    armed,
+                    inhibited: alert_system.is_inhibited(),
+                    failed: alert_system.is_failed() || failed,
+                    skipped,
+                },
+            );
         }
 
-        alert_state
+        // the crew is presented the managed alerts, which are stable over successive frames
+        let alert_state = self.alert_manager.update(
+            state.timestamp,
+            taws_state.alert_state(),
+            |alert| taws_state.function(alert).inhibited,
+            |alert| taws_state.function(alert).failed,
+            &self.config,
+        );
+        taws_state.set_alert_state(
+            alert_state,
+            self.alert_manager.events(),
+            self.alert_manager.timings(),
+        );
+
+        taws_state
+            .alert_state_mut()
+            .set_external_alerts(external_alerts);
+
+        let voice_message = self.aural.update(state.timestamp, taws_state.alert_state());
+        taws_state.set_voice_message(voice_message, self.aural.silenced());
+
+        taws_state
     }
 }
 
@@ -247,7 +469,8 @@ mod test {
 
     #[test]
     fn check_all_alert_systems() {
-        let taws = Taws::new(Default::default());
+        let taws = Taws::new(Default::default()).unwrap();
+        let _ = taws.is_armed(Alert::BankAngle);
         let _ = taws.is_armed(Alert::Ffac);
         let _ = taws.is_armed(Alert::Flta);
         let _ = taws.is_armed(Alert::Mode1);
@@ -255,6 +478,111 @@ mod test {
         let _ = taws.is_armed(Alert::Mode3);
         let _ = taws.is_armed(Alert::Mode4);
         let _ = taws.is_armed(Alert::Mode5);
+        let _ = taws.is_armed(Alert::Mode6);
+        let _ = taws.is_armed(Alert::Mode7);
         let _ = taws.is_armed(Alert::Pda);
+        let _ = taws.is_armed(Alert::TailStrike);
+    }
+
+    #[test]
+    fn failed_input_disarms_and_silences_mode1() {
+        let mut taws = Taws::new(TawsConfig {
+            signal_test: false,
+            ..Default::default()
+        })
+        .unwrap();
+        let sink_rate = AircraftState {
+            altitude_ground: Length::new::<foot>(1000.0),
+            climb_rate: Velocity::new::<foot_per_minute>(-6000.0),
+            ..Default::default()
+        };
+
+        taws.process(&AircraftState::default(), &[]);
+        let taws_state = taws.process(&sink_rate, &[]);
+        assert!(taws_state.alert_state().level(Alert::Mode1).is_some());
+
+        // the input is stale after five seconds without an aircraft state
+        let taws_state = taws.process(
+            &AircraftState {
+                timestamp: Time::new::<second>(5.0),
+                ..sink_rate
+            },
+            &[],
+        );
+        assert!(!taws.is_armed(Alert::Mode1));
+        let status = taws_state.function(Alert::Mode1);
+        assert!(!status.armed && status.failed);
+        assert_eq!(taws_state.alert_state().level(Alert::Mode1), None);
+    }
+
+    #[test]
+    fn arm_state_is_kept_over_a_failure() {
+        let mut taws = Taws::new(Default::default()).unwrap();
+        let state = |time| AircraftState {
+            timestamp: Time::new::<second>(time),
+            altitude_ground: Length::new::<foot>(3000.0),
+            ..Default::default()
+        };
+
+        taws.process(&state(0.0), &[]);
+        taws.process(&state(5.0), &[]);
+        assert!(!taws.is_armed(Alert::Mode1));
+        assert!(!taws.is_armed(Alert::Mode2));
+
+        // the crew disarms Mode 2 while the input is stale
+        taws.disarm(Alert::Mode2);
+        let taws_state = taws.process(&state(6.0), &[]);
+        assert!(taws.is_armed(Alert::Mode1));
+        assert!(!taws.is_armed(Alert::Mode2));
+        assert!(taws_state.function(Alert::Mode1).armed);
+        assert!(!taws_state.function(Alert::Mode2).armed);
+    }
+
+    #[test]
+    fn no_mode3_caution_in_a_low_circuit() {
+        let mut taws = Taws::new(TawsConfig {
+            signal_test: false,
+            ..Default::default()
+        })
+        .unwrap();
+
+        // roll, climb to 800 foot, fly downwind and descend to land in landing configuration
+        for frame in 0..1900 {
+            let time = frame as f64 * 0.1;
+            let (height, climb_rate, landing) = match time {
+                t if t < 20.0 => (0.0, 0.0, false),
+                t if t < 68.0 => ((t - 20.0) * 1000.0 / 60.0, 1000.0, false),
+                t if t < 128.0 => (800.0, 0.0, false),
+                t => (800.0 - (t - 128.0) * 700.0 / 60.0, -700.0, true),
+            };
+            let state = AircraftState {
+                timestamp: Time::new::<second>(time),
+                altitude: Length::new::<foot>(height),
+                altitude_ground: Length::new::<foot>(height),
+                climb_rate: Velocity::new::<foot_per_minute>(climb_rate),
+                speed_ground: Velocity::new::<knot>(120.0),
+                speed_air: Velocity::new::<knot>(120.0),
+                landing_gear: if landing {
+                    GearPosition::Down
+                } else {
+                    GearPosition::Up
+                },
+                flaps: if landing {
+                    FlapPosition::Landing
+                } else {
+                    FlapPosition::Intermediate
+                },
+                ..Default::default()
+            };
+
+            let taws_state = taws.process(&state, &[]);
+            assert_eq!(
+                taws_state.alert_state().level(Alert::Mode3),
+                None,
+                "{}",
+                state
+            );
+        }
+        assert!(!taws.is_armed(Alert::Mode3));
     }
 }
@@ -1,8 +1,7 @@
 // Allow us to iterate over the functionalities for a lack of inline comptime loop unrolling
 macro_rules! functionalities {
     [$( $functionality_name:tt ),+] => {
-        ///
-        fn get_functionality(&self, alert_system: Alert) -> &dyn AlertSystem {
+        fn get_functionality(&self, alert_system: Alert) -> &dyn AlertSystem<'a> {
             match alert_system {
             $(
                 $crate::alerts::Alert::$functionality_name => casey::lower!(&self.$functionality_name),
@@ -10,7 +9,7 @@ macro_rules! functionalities {
             }
         }
 
-        fn get_mut_functionality(&mut self, alert_system: Alert) -> &mut dyn AlertSystem {
+        fn get_mut_functionality(&mut self, alert_system: Alert) -> &mut dyn AlertSystem<'a> {
             match alert_system {
             $(
                 $crate::alerts::Alert::$functionality_name => casey::lower!(&mut self.$functionality_name),
@@ -18,11 +17,11 @@ macro_rules! functionalities {
             }
         }
 
-        fn functionality_mut_array(&mut self)->[(Alert, &mut dyn AlertSystem); count!($($functionality_name)+)]{
+        fn functionality_mut_array(&mut self)->[(Alert, &mut dyn AlertSystem<'a>); count!($($functionality_name)+)]{
             [ $(
                 (
                     $crate::alerts::Alert::$functionality_name,
-                    casey::lower!(&mut self.$functionality_name) as &mut dyn AlertSystem
+                    casey::lower!(&mut self.$functionality_name) as &mut dyn AlertSystem<'a>
                 ),
             )+ ]
         }
@@ -4,8 +4,18 @@
 //! opentaws::prelude::*`.
 
 pub use crate::{
-    alerts::{Alert, AlertLevel, AlertState, AlertSystem},
-    types::{AircraftState, TawsConfig},
+    alert_manager::{AlertEvent, AlertEvents, AlertTiming},
+    alerts::{Alert, AlertLevel, AlertState, AlertSubType, AlertSystem, Callout},
+    aural::VoiceMessage,
+    flight_phase::FlightPhase,
+    priorities::{AlertPriorities, ExternalAlert, PrioritizedAlert},
+    self_test::{SelfTestFailure, SelfTestFailures, SelfTestReport, SelfTestStep},
+    signal_test::{InputFaults, UnplausibleSignal},
+    taws_state::{FunctionStatus, TawsState},
+    terrain_server::{Identifier, Position, Runway, RunwayServer, TerrainError, TerrainServer},
+    types::{
+        AircraftState, ApproachType, ConfigError, Discrete, FlapPosition, GearPosition, TawsConfig,
+    },
     Taws,
 };
 
@@ -0,0 +1,287 @@
+//! Ranks concurrent alerts, including those of systems outside of the TAWS
+
+use crate::prelude::*;
+
+/// Alerts of systems outside of the TAWS, which take part in the alert prioritization
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExternalAlert {
+    /// Predictive windshear warning ("GO AROUND WINDSHEAR AHEAD")
+    PredictiveWindshearWarning,
+
+    /// Predictive windshear caution ("MONITOR RADAR DISPLAY")
+    PredictiveWindshearCaution,
+
+    /// TCAS resolution advisory ("CLIMB", "DESCEND", etc.)
+    TcasResolutionAdvisory,
+
+    /// TCAS traffic advisory ("TRAFFIC TRAFFIC")
+    TcasTrafficAdvisory,
+
+    /// Runway awareness and advisory system advisory ("APPROACHING RUNWAY", etc.)
+    RunwayAwarenessAdvisory,
+
+    /// Alert of another system which brings its own priority, on the scale of `AlertPriorities`
+    ///
+    /// It suppresses the TAWS alerts whose priority is a greater value, regardless of
+    /// `TawsConfig::alert_priorities`.
+    Other(u8),
+}
+
+impl ExternalAlert {
+    /// Number of different external alerts which can be active at once, further ones are ignored
+    pub const COUNT: usize = 8;
+}
+
+/// One entry of the alert prioritization
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrioritizedAlert {
+    /// An alert of the TAWS, a sub-type of `None` ranks all sub-types without an entry of their own
+    Taws(Alert, AlertLevel, Option<AlertSubType>),
+
+    /// An alert of a system outside of the TAWS
+    External(ExternalAlert),
+}
+
+/// Ordered list of the alerts, from the highest to the lowest priority
+///
+/// The priority of an alert is its position in the list, so a low value means a high priority.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AlertPriorities {
+    list: &'static [PrioritizedAlert],
+}
+
+impl AlertPriorities {
+    /// Prioritization of DO-367 Table 2-19, extended by the optional alerts of this TAWS
+    ///
+    /// Entries of the table which have no counterpart here, e.g. the V1 and engine out callouts,
+    /// are left out. The TCAS RA ranks below the warnings, so that the TAWS does not talk over it
+    /// with a caution or callout. The runway awareness advisories rank last.
+    pub const DO_367: AlertPriorities = AlertPriorities::new(&[
+        PrioritizedAlert::Taws(Alert::Mode7, AlertLevel::Warning, None),
+        PrioritizedAlert::Taws(Alert::Mode1, AlertLevel::Warning, None),
+        PrioritizedAlert::Taws(Alert::Mode2, AlertLevel::Warning, None),
+        PrioritizedAlert::Taws(Alert::Flta, AlertLevel::Warning, None),
+        PrioritizedAlert::External(ExternalAlert::PredictiveWindshearWarning),
+        PrioritizedAlert::External(ExternalAlert::TcasResolutionAdvisory),
+        PrioritizedAlert::Taws(Alert::Mode2, AlertLevel::Caution, None),
+        PrioritizedAlert::Taws(
+            Alert::Mode6,
+            AlertLevel::Annunciation,
+            Some(AlertSubType::Callout(Callout::Minimums)),
+        ),
+        PrioritizedAlert::Taws(Alert::Flta, AlertLevel::Caution, None),
+        PrioritizedAlert::Taws(
+            Alert::Mode4,
+            AlertLevel::Caution,
+            Some(AlertSubType::TooLowTerrain),
+        ),
+        PrioritizedAlert::Taws(Alert::Pda, AlertLevel::Caution, None),
+        PrioritizedAlert::Taws(Alert::Mode6, AlertLevel::Annunciation, None),
+        PrioritizedAlert::Taws(Alert::Ffac, AlertLevel::Annunciation, None),
+        PrioritizedAlert::Taws(
+            Alert::Mode4,
+            AlertLevel::Caution,
+            Some(AlertSubType::TooLowGear),
+        ),
+        PrioritizedAlert::Taws(
+            Alert::Mode4,
+            AlertLevel::Caution,
+            Some(AlertSubType::TooLowFlaps),
+        ),
+        PrioritizedAlert::Taws(Alert::Mode1, AlertLevel::Caution, None),
+        PrioritizedAlert::Taws(Alert::Mode3, AlertLevel::Caution, None),
+        PrioritizedAlert::Taws(Alert::Mode5, AlertLevel::Caution, None),
+        PrioritizedAlert::External(ExternalAlert::PredictiveWindshearCaution),
+        PrioritizedAlert::Taws(Alert::BankAngle, AlertLevel::Warning, None),
+        PrioritizedAlert::Taws(Alert::BankAngle, AlertLevel::Caution, None),
+        PrioritizedAlert::Taws(Alert::Mode7, AlertLevel::Caution, None),
+        PrioritizedAlert::Taws(Alert::TailStrike, AlertLevel::Caution, None),
+        PrioritizedAlert::External(ExternalAlert::TcasTrafficAdvisory),
+        PrioritizedAlert::External(ExternalAlert::RunwayAwarenessAdvisory),
+    ]);
+
+    /// Create a prioritization from a list of alerts, ordered from the highest to the lowest
+    /// priority
+    ///
+    /// Only the first 256 entries are taken into account.
+    pub const fn new(list: &'static [PrioritizedAlert]) -> Self {
+        Self { list }
+    }
+
+    /// Get an iterator to the alerts, from the highest to the lowest priority
+    ///
+    /// This allows to derive an alternate prioritization from an existing one.
+    pub fn iter(&self) -> impl Iterator<Item = PrioritizedAlert> + '_ {
+        self.list.iter().copied()
+    }
+
+    /// Get the priority of an alert of the TAWS, `None` if it is not listed
+    pub fn priority(
+        &self,
+        alert: Alert,
+        level: AlertLevel,
+        sub_type: Option<AlertSubType>,
+    ) -> Option<u8> {
+        let exact = PrioritizedAlert::Taws(alert, level, sub_type);
+        let any_sub_type = PrioritizedAlert::Taws(alert, level, None);
+
+        self.position(|entry| entry == exact)
+            .or_else(|| self.position(|entry| entry == any_sub_type))
+    }
+
+    /// Get the priority of an alert of a system outside of the TAWS, `None` if it is not listed
+    ///
+    /// The priority of `ExternalAlert::Other` is its own, whether it is listed or not.
+    pub fn external_priority(&self, alert: ExternalAlert) -> Option<u8> {
+        match alert {
+            ExternalAlert::Other(priority) => Some(priority),
+            _ => self.position(|entry| entry == PrioritizedAlert::External(alert)),
+        }
+    }
+
+    /// Get an iterator to the alerts which the alert systems may emit, but which are not listed
+    ///
+    /// An installation must not use a prioritization for which this yields anything.
+    pub fn unprioritized(
+        &self,
+    ) -> impl Iterator<Item = (Alert, AlertLevel, Option<AlertSubType>)> + '_ {
+        Alert::ALL
+            .iter()
+            .flat_map(|alert| {
+                alert
+                    .outputs()
+                    .iter()
+                    .map(move |(level, sub_type)| (*alert, *level, *sub_type))
+            })
+            .filter(move |(alert, level, sub_type)| {
+                self.priority(*alert, *level, *sub_type).is_none()
+            })
+    }
+
+    fn position(&self, predicate: impl Fn(PrioritizedAlert) -> bool) -> Option<u8> {
+        self.list
+            .iter()
+            .take(usize::from(u8::MAX) + 1)
+            .position(|entry| predicate(*entry))
+            .map(|position| position as u8)
+    }
+}
+
+impl Default for AlertPriorities {
+    fn default() -> Self {
+        Self::DO_367
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn do_367_prioritizes_all_alerts() {
+        let unprioritized: Vec<_> = AlertPriorities::DO_367.unprioritized().collect();
+        assert_eq!(unprioritized, []);
+    }
+
+    #[test]
+    fn do_367_order() {
+        use AlertLevel::*;
+        use AlertSubType::{TooLowFlaps, TooLowGear, TooLowTerrain};
+
+        let expected = [
+            PrioritizedAlert::Taws(Alert::Mode7, Warning, None),
+            PrioritizedAlert::Taws(Alert::Mode1, Warning, None),
+            PrioritizedAlert::Taws(Alert::Mode2, Warning, None),
+            PrioritizedAlert::Taws(Alert::Flta, Warning, None),
+            PrioritizedAlert::External(ExternalAlert::PredictiveWindshearWarning),
+            PrioritizedAlert::External(ExternalAlert::TcasResolutionAdvisory),
+            PrioritizedAlert::Taws(Alert::Mode2, Caution, None),
+            PrioritizedAlert::Taws(
+                Alert::Mode6,
+                Annunciation,
+                Some(AlertSubType::Callout(Callout::Minimums)),
+            ),
+            PrioritizedAlert::Taws(Alert::Flta, Caution, None),
+            PrioritizedAlert::Taws(Alert::Mode4, Caution, Some(TooLowTerrain)),
+            PrioritizedAlert::Taws(Alert::Pda, Caution, None),
+            PrioritizedAlert::Taws(Alert::Mode6, Annunciation, None),
+            PrioritizedAlert::Taws(Alert::Ffac, Annunciation, None),
+            PrioritizedAlert::Taws(Alert::Mode4, Caution, Some(TooLowGear)),
+            PrioritizedAlert::Taws(Alert::Mode4, Caution, Some(TooLowFlaps)),
+            PrioritizedAlert::Taws(Alert::Mode1, Caution, None),
+            PrioritizedAlert::Taws(Alert::Mode3, Caution, None),
+            PrioritizedAlert::Taws(Alert::Mode5, Caution, None),
+            PrioritizedAlert::External(ExternalAlert::PredictiveWindshearCaution),
+            PrioritizedAlert::Taws(Alert::BankAngle, Warning, None),
+            PrioritizedAlert::Taws(Alert::BankAngle, Caution, None),
+            PrioritizedAlert::Taws(Alert::Mode7, Caution, None),
+            PrioritizedAlert::Taws(Alert::TailStrike, Caution, None),
+            PrioritizedAlert::External(ExternalAlert::TcasTrafficAdvisory),
+            PrioritizedAlert::External(ExternalAlert::RunwayAwarenessAdvisory),
+        ];
+
+        let list: Vec<_> = AlertPriorities::DO_367.iter().collect();
+        assert_eq!(list, expected);
+    }
+
+    #[test]
+    fn sub_types_fall_back_to_their_alert() {
+        let priorities = AlertPriorities::DO_367;
+        let priority =
+            |sub_type| priorities.priority(Alert::Mode6, AlertLevel::Annunciation, sub_type);
+
+        let minimums = priority(Some(AlertSubType::Callout(Callout::Minimums)));
+        let five_hundred = priority(Some(AlertSubType::Callout(Callout::FiveHundred)));
+        assert!(minimums < five_hundred);
+        assert_eq!(five_hundred, priority(None));
+    }
+
+    #[test]
+    fn tcas_ra_outranks_cautions() {
+        let priorities = AlertPriorities::DO_367;
+        let ra = priorities.external_priority(ExternalAlert::TcasResolutionAdvisory);
+
+        assert!(priorities.priority(Alert::Flta, AlertLevel::Warning, None) < ra);
+        assert!(priorities.priority(Alert::Mode2, AlertLevel::Caution, None) > ra);
+        assert!(priorities.priority(Alert::Mode6, AlertLevel::Annunciation, None) > ra);
+    }
+
+    #[test]
+    fn other_external_alerts_bring_their_priority() {
+        let priorities = AlertPriorities::DO_367;
+
+        assert_eq!(
+            priorities.external_priority(ExternalAlert::Other(3)),
+            Some(3)
+        );
+        assert_eq!(
+            AlertPriorities::new(&[]).external_priority(ExternalAlert::Other(3)),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn unlisted_alerts_are_found() {
+        let priorities = AlertPriorities::new(&[
+            PrioritizedAlert::External(ExternalAlert::TcasResolutionAdvisory),
+            PrioritizedAlert::Taws(Alert::Mode3, AlertLevel::Caution, None),
+        ]);
+
+        assert_eq!(
+            priorities.external_priority(ExternalAlert::TcasResolutionAdvisory),
+            Some(0)
+        );
+        assert_eq!(
+            priorities.priority(Alert::Mode3, AlertLevel::Caution, None),
+            Some(1)
+        );
+        assert!(priorities
+            .unprioritized()
+            .any(|unprioritized| unprioritized == (Alert::Mode1, AlertLevel::Warning, None)));
+        assert!(!priorities
+            .unprioritized()
+            .any(|(alert, _, _)| alert == Alert::Mode3));
+    }
+}
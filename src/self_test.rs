@@ -0,0 +1,479 @@
+//! Continuous and initiated tests of the TAWS itself (DO-367 MOPS_001 to MOPS_006)
+
+use core::fmt;
+
+use crate::alerts::{functionalities::Mode6, ALERT_STATE_SIZE};
+use crate::aural::AuralScheduler;
+use crate::prelude::*;
+use crate::signal_test::SignalTest;
+
+/// Capabilities verified by the initiated self-test, in the order in which they are tested
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SelfTestStep {
+    /// The voice message of the warning with the highest configured priority is played before a
+    /// callout
+    AuralAlerting,
+
+    /// The alerts are ordered by their configured priority for the visual alerting
+    VisualAlerting,
+
+    /// Mode 6 annunciates each configured callout during a descent
+    AltitudeCallouts,
+
+    /// The terrain database provides plausible elevations for the terrain display
+    TerrainDisplay,
+
+    /// Implausible input data is detected and reported, which requires the signal test to be
+    /// enabled
+    FaultReporting,
+}
+
+impl fmt::Display for SelfTestStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SelfTestStep::AuralAlerting => "aural alerting",
+            SelfTestStep::VisualAlerting => "visual alerting",
+            SelfTestStep::AltitudeCallouts => "altitude callouts",
+            SelfTestStep::TerrainDisplay => "terrain display outputs",
+            SelfTestStep::FaultReporting => "fault reporting",
+        };
+        f.write_str(name)
+    }
+}
+
+impl SelfTestStep {
+    /// Number of different steps
+    pub const COUNT: usize = 5;
+
+    /// All steps, in the order in which they are tested
+    pub const ALL: [SelfTestStep; SelfTestStep::COUNT] = [
+        SelfTestStep::AuralAlerting,
+        SelfTestStep::VisualAlerting,
+        SelfTestStep::AltitudeCallouts,
+        SelfTestStep::TerrainDisplay,
+        SelfTestStep::FaultReporting,
+    ];
+}
+
+/// Failures found by the continuous or the initiated self-test
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SelfTestFailure {
+    /// The aircraft states arrive too seldom for alerts to be emitted in time
+    StaleInput,
+
+    /// The terrain database is unavailable or provides implausible elevations
+    TerrainDatabase,
+
+    /// The internal data of an alert system, e.g. one of its envelopes, is inconsistent
+    Integrity(Alert),
+
+    /// A step of the initiated self-test failed, this persists until the next initiated self-test
+    Initiated(SelfTestStep),
+}
+
+impl fmt::Display for SelfTestFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelfTestFailure::StaleInput => write!(f, "input data stale"),
+            SelfTestFailure::TerrainDatabase => write!(f, "terrain database failure"),
+            SelfTestFailure::Integrity(alert) => write!(f, "{:?} integrity failure", alert),
+            SelfTestFailure::Initiated(step) => write!(f, "{} test failure", step),
+        }
+    }
+}
+
+impl SelfTestFailure {
+    /// Whether an alert system must be disarmed due to the failure
+    pub fn affects(&self, alert: Alert) -> bool {
+        use SelfTestStep::*;
+
+        match self {
+            SelfTestFailure::StaleInput => true,
+            SelfTestFailure::TerrainDatabase => alert == Alert::Flta,
+            SelfTestFailure::Integrity(failed) => alert == *failed,
+            SelfTestFailure::Initiated(AuralAlerting | VisualAlerting | FaultReporting) => true,
+            SelfTestFailure::Initiated(AltitudeCallouts) => alert == Alert::Mode6,
+            SelfTestFailure::Initiated(TerrainDisplay) => alert == Alert::Flta,
+        }
+    }
+}
+
+/// The maximum number of different `SelfTestFailure`s
+const MAX_FAILURES: usize = 2 + ALERT_STATE_SIZE + SelfTestStep::COUNT;
+
+/// Set of the failures found by the self-test
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SelfTestFailures {
+    failures: [Option<SelfTestFailure>; MAX_FAILURES],
+}
+
+impl Default for SelfTestFailures {
+    fn default() -> Self {
+        Self {
+            failures: [None; MAX_FAILURES],
+        }
+    }
+}
+
+impl SelfTestFailures {
+    /// Adds a failure, adding the same failure twice has no effect
+    pub fn insert(&mut self, failure: SelfTestFailure) {
+        if self.contains(failure) {
+            return;
+        }
+        if let Some(option) = self.failures.iter_mut().find(|e| e.is_none()) {
+            *option = Some(failure);
+        }
+    }
+
+    /// Whether a specific failure was found
+    pub fn contains(&self, failure: SelfTestFailure) -> bool {
+        self.iter().any(|existing| existing == failure)
+    }
+
+    /// `true` if no failure was found
+    pub fn is_empty(&self) -> bool {
+        self.failures.iter().all(Option::is_none)
+    }
+
+    /// Whether any of the failures requires an alert system to be disarmed
+    pub fn affect(&self, alert: Alert) -> bool {
+        self.iter().any(|failure| failure.affects(alert))
+    }
+
+    /// Get an iterator to the failures
+    pub fn iter(&self) -> impl Iterator<Item = SelfTestFailure> + '_ {
+        self.failures.iter().flatten().copied()
+    }
+}
+
+/// Result of the initiated self-test, one entry for each `SelfTestStep`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SelfTestReport {
+    results: [(SelfTestStep, bool); SelfTestStep::COUNT],
+}
+
+impl SelfTestReport {
+    /// `true` if all steps passed
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|(_, passed)| *passed)
+    }
+
+    /// Whether a specific step passed
+    pub fn step_passed(&self, step: SelfTestStep) -> bool {
+        self.iter().any(|(tested, passed)| tested == step && passed)
+    }
+
+    /// Get an iterator to the steps and whether they passed, in the order in which they were
+    /// tested
+    pub fn iter(&self) -> impl Iterator<Item = (SelfTestStep, bool)> + '_ {
+        self.results.iter().copied()
+    }
+
+    /// Get an iterator to the steps which failed
+    pub fn failures(&self) -> impl Iterator<Item = SelfTestStep> + '_ {
+        self.iter()
+            .filter(|(_, passed)| !passed)
+            .map(|(step, _)| step)
+    }
+}
+
+/// Monitors the TAWS continuously and runs the initiated self-test
+#[derive(Debug, Default)]
+pub(crate) struct SelfTest {
+    last_timestamp: Option<Time>,
+    last_position: Option<Position>,
+
+    /// Failures found by the continuous self-test for the last aircraft state
+    monitored: SelfTestFailures,
+
+    /// Failures of the last initiated self-test
+    initiated: SelfTestFailures,
+}
+
+impl SelfTest {
+    /// Longest time in seconds between two aircraft states before the input is considered stale
+    const MAX_INPUT_INTERVAL: f64 = 2.0;
+
+    /// Lowest plausible terrain elevation in foot, below the shore of the Dead Sea
+    const MIN_ELEVATION: f64 = -1500.0;
+
+    /// Highest plausible terrain elevation in foot, above the summit of Mount Everest
+    const MAX_ELEVATION: f64 = 29100.0;
+
+    /// All failures which are currently present, from both the continuous and the initiated
+    /// self-test
+    pub fn failures(&self) -> SelfTestFailures {
+        let mut failures = self.monitored;
+        for failure in self.initiated.iter() {
+            failures.insert(failure);
+        }
+        failures
+    }
+
+    /// Checks the input freshness and the terrain database
+    ///
+    /// The failures found by the integrity checks of the alert systems are handed in, as they are
+    /// kept together with the failures found here until the next aircraft state.
+    pub fn monitor(
+        &mut self,
+        state: &AircraftState,
+        terrain_server: Option<&dyn TerrainServer>,
+        mut failures: SelfTestFailures,
+    ) {
+        let max_interval = Time::new::<second>(Self::MAX_INPUT_INTERVAL);
+        if let Some(last_timestamp) = self.last_timestamp.replace(state.timestamp) {
+            if state.timestamp - last_timestamp > max_interval {
+                failures.insert(SelfTestFailure::StaleInput);
+            }
+        }
+
+        let position = Position::from(state);
+        if !Self::terrain_plausible(terrain_server, &position) {
+            failures.insert(SelfTestFailure::TerrainDatabase);
+        }
+        self.last_position = Some(position);
+
+        self.monitored = failures;
+    }
+
+    /// Steps through the capabilities of the TAWS, remembering the failed steps
+    ///
+    /// The steps run on copies of the signal test of the TAWS, so that they do not disturb it.
+    pub fn initiate(&mut self, config: &TawsConfig, signal_test: &SignalTest) -> SelfTestReport {
+        let position = self.last_position.unwrap_or(Position {
+            latitude: Angle::new::<degree>(0.0),
+            longitude: Angle::new::<degree>(0.0),
+            altitude_sea: Length::new::<foot>(0.0),
+        });
+
+        let results = SelfTestStep::ALL.map(|step| {
+            let passed = match step {
+                SelfTestStep::AuralAlerting => Self::aural_alerting(config.alert_priorities),
+                SelfTestStep::VisualAlerting => Self::visual_alerting(config.alert_priorities),
+                SelfTestStep::AltitudeCallouts => Self::altitude_callouts(config),
+                SelfTestStep::TerrainDisplay => {
+                    Self::terrain_plausible(config.terrain_server, &position)
+                }
+                SelfTestStep::FaultReporting => {
+                    config.signal_test && Self::fault_reporting(signal_test)
+                }
+            };
+            (step, passed)
+        });
+        let report = SelfTestReport { results };
+
+        self.initiated = SelfTestFailures::default();
+        for step in report.failures() {
+            self.initiated.insert(SelfTestFailure::Initiated(step));
+        }
+
+        report
+    }
+
+    /// `true` if no terrain database is installed, or if it provides a plausible elevation
+    fn terrain_plausible(terrain_server: Option<&dyn TerrainServer>, position: &Position) -> bool {
+        match terrain_server.map(|terrain_server| terrain_server.elevation(position)) {
+            None | Some(Err(TerrainError::NoData)) => true,
+            Some(Err(TerrainError::Unavailable)) => false,
+            Some(Ok(elevation)) => {
+                (Self::MIN_ELEVATION..=Self::MAX_ELEVATION).contains(&elevation.get::<foot>())
+            }
+        }
+    }
+
+    /// The voice message scheduler starts the voice message of the warning with the highest
+    /// priority rather than a callout, using the configured priorities
+    fn aural_alerting(priorities: AlertPriorities) -> bool {
+        let warning = priorities.iter().find_map(|entry| match entry {
+            PrioritizedAlert::Taws(alert, AlertLevel::Warning, sub_type) => {
+                VoiceMessage::for_alert(alert, AlertLevel::Warning, sub_type)
+                    .map(|message| (alert, sub_type, message))
+            }
+            _ => None,
+        });
+        let (alert, sub_type, message) = match warning {
+            Some(warning) => warning,
+            None => return false,
+        };
+
+        let mut alert_state = AlertState::new(priorities);
+        let callout = AlertSubType::Callout(Callout::FiveHundred);
+        alert_state.insert(Alert::Mode6, AlertLevel::Annunciation, Some(callout));
+        alert_state.insert(alert, AlertLevel::Warning, sub_type);
+
+        AuralScheduler::default().update(Time::new::<second>(0.0), &alert_state) == Some(message)
+    }
+
+    /// The alerts are presented by their configured priority, not by the order in which they
+    /// occurred
+    fn visual_alerting(priorities: AlertPriorities) -> bool {
+        // against the order of `Alert::ALL`, which resembles the priorities
+        let mut alert_state = AlertState::new(priorities);
+        for alert in Alert::ALL.iter().rev() {
+            if let Some((level, sub_type)) = alert.outputs().first() {
+                alert_state.insert(*alert, *level, *sub_type);
+            }
+        }
+
+        let mut last_priority = None;
+        let mut presented = 0;
+        let ordered = alert_state.iter().all(|(alert, level)| {
+            let priority = alert_state.priority(alert, level, alert_state.sub_type(alert));
+            presented += 1;
+            !matches!(last_priority.replace(priority), Some(last) if last > priority)
+        });
+
+        ordered && presented == Alert::COUNT
+    }
+
+    /// A separate instance of Mode 6 annunciates every configured callout during a descent
+    ///
+    /// Callouts at the same height are annunciated as one.
+    fn altitude_callouts(config: &TawsConfig) -> bool {
+        let heights = config.mode6_callouts.iter().filter_map(|callout| {
+            callout
+                .height()
+                .or(config.decision_height.map(|h| h.get::<foot>()))
+        });
+        let expected = heights
+            .clone()
+            .enumerate()
+            .filter(|(i, height)| heights.clone().take(*i).all(|other| other != *height))
+            .count();
+        let top = heights.fold(0.0, f64::max) + 100.0;
+
+        // descend in steps of one foot, so that no callout is skipped
+        let mut mode6 = Mode6::new(config);
+        let mut annunciated = 0;
+        let mut height = top;
+        while height >= 0.0 {
+            let state = AircraftState {
+                altitude_ground: Length::new::<foot>(height),
+                ..Default::default()
+            };
+            if mode6.process(&state).is_some() {
+                annunciated += 1;
+            }
+            height -= 1.0;
+        }
+
+        annunciated == expected
+    }
+
+    /// A copy of the signal test detects an aircraft state below the terrain
+    fn fault_reporting(signal_test: &SignalTest) -> bool {
+        let below_terrain = AircraftState {
+            altitude_ground: Length::new::<foot>(-10.0),
+            ..Default::default()
+        };
+
+        signal_test
+            .clone()
+            .check(&below_terrain)
+            .contains(UnplausibleSignal::AltitudeGround)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Failed;
+
+    impl TerrainServer for Failed {
+        fn elevation(&self, _position: &Position) -> Result<Length, TerrainError> {
+            Err(TerrainError::Unavailable)
+        }
+    }
+
+    fn state(time: f64) -> AircraftState {
+        AircraftState {
+            timestamp: Time::new::<second>(time),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn stale_input() {
+        let mut self_test = SelfTest::default();
+
+        self_test.monitor(&state(0.0), None, Default::default());
+        self_test.monitor(&state(1.0), None, Default::default());
+        assert!(self_test.failures().is_empty());
+
+        self_test.monitor(&state(5.0), None, Default::default());
+        assert!(self_test.failures().contains(SelfTestFailure::StaleInput));
+        assert!(self_test.failures().affect(Alert::Mode1));
+
+        // the failure is gone with the next timely aircraft state
+        self_test.monitor(&state(6.0), None, Default::default());
+        assert!(self_test.failures().is_empty());
+    }
+
+    #[test]
+    fn unavailable_terrain_database() {
+        let mut self_test = SelfTest::default();
+        self_test.monitor(&state(0.0), Some(&Failed), Default::default());
+
+        let failures = self_test.failures();
+        assert!(failures.contains(SelfTestFailure::TerrainDatabase));
+        assert!(failures.affect(Alert::Flta));
+        assert!(!failures.affect(Alert::Mode1));
+    }
+
+    #[test]
+    fn initiated_self_test_passes() {
+        let mut self_test = SelfTest::default();
+        let report = self_test.initiate(&TawsConfig::default(), &Default::default());
+
+        assert!(report.passed());
+        assert!(SelfTestStep::ALL
+            .iter()
+            .all(|step| report.step_passed(*step)));
+        assert!(self_test.failures().is_empty());
+    }
+
+    #[test]
+    fn initiated_self_test_failures_persist() {
+        let mut self_test = SelfTest::default();
+        let config = TawsConfig {
+            terrain_server: Some(&Failed),
+            ..Default::default()
+        };
+        let report = self_test.initiate(&config, &Default::default());
+
+        assert!(report.failures().eq([SelfTestStep::TerrainDisplay]));
+        assert!(self_test
+            .failures()
+            .contains(SelfTestFailure::Initiated(SelfTestStep::TerrainDisplay)));
+    }
+
+    #[test]
+    fn fault_reporting_needs_the_signal_test() {
+        let mut self_test = SelfTest::default();
+        let config = TawsConfig {
+            signal_test: false,
+            ..Default::default()
+        };
+        let report = self_test.initiate(&config, &Default::default());
+
+        assert!(report.failures().eq([SelfTestStep::FaultReporting]));
+    }
+
+    #[test]
+    fn aural_alerting_uses_the_configured_priorities() {
+        let callout_first = AlertPriorities::new(&[
+            PrioritizedAlert::Taws(Alert::Mode6, AlertLevel::Annunciation, None),
+            PrioritizedAlert::Taws(Alert::Mode1, AlertLevel::Warning, None),
+        ]);
+
+        assert!(SelfTest::aural_alerting(AlertPriorities::DO_367));
+        assert!(!SelfTest::aural_alerting(callout_first));
+    }
+}
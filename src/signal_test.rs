@@ -1,67 +1,294 @@
-//! This file shall provide filtering
+//! Plausibility checks for the input data, applied before the alert systems see it
+
+use core::fmt;
 
 use crate::prelude::*;
-use ringbuffer::{AllocRingBuffer, RingBuffer};
-use uom::si::ratio::{ratio, Ratio};
 
-// TODO: What is wrong, if two values do not fit together?
-#[derive(Copy, Clone, Debug, PartialEq)]
+/// Reasons why an `AircraftState` is rejected as not plausible
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UnplausibleSignal {
+    /// The height above terrain is negative
     AltitudeGround,
+
+    /// The altitude changed faster than any aircraft climbs or descends
     AltitudeSea,
+
+    /// The airspeed changed faster than any aircraft accelerates
     SpeedAir,
+
+    /// The ground speed changed faster than any aircraft accelerates
     SpeedGround,
-    NotEnoughData,
-    //InternalError,
+
     /// Time stamp is either equally aged or older than the last
     Anachronistic,
+
+    /// Time stamp is too far ahead of the last, the input data arrives too seldom or was
+    /// interrupted
+    Stale,
+}
+
+impl fmt::Display for UnplausibleSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnplausibleSignal::AltitudeGround => write!(f, "negative height above terrain"),
+            UnplausibleSignal::AltitudeSea => write!(f, "implausible altitude change"),
+            UnplausibleSignal::SpeedAir => write!(f, "implausible airspeed change"),
+            UnplausibleSignal::SpeedGround => write!(f, "implausible ground speed change"),
+            UnplausibleSignal::Anachronistic => write!(f, "timestamp not increasing"),
+            UnplausibleSignal::Stale => write!(f, "input data stale"),
+        }
+    }
+}
+
+impl UnplausibleSignal {
+    /// Number of variants
+    pub const COUNT: usize = 6;
+
+    /// Whether an alert system relies on the signal, and thus must not process the aircraft state
+    pub fn affects(&self, alert: Alert) -> bool {
+        use Alert::*;
+
+        match self {
+            UnplausibleSignal::Anachronistic | UnplausibleSignal::Stale => true,
+            // only FLTA works without the height above terrain
+            UnplausibleSignal::AltitudeGround => alert != Flta,
+            UnplausibleSignal::AltitudeSea => matches!(alert, Ffac | Flta | Mode3),
+            UnplausibleSignal::SpeedAir => matches!(alert, Mode2 | Mode4 | Mode7),
+            UnplausibleSignal::SpeedGround => matches!(alert, Flta | Mode7),
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            UnplausibleSignal::AltitudeGround => 0,
+            UnplausibleSignal::AltitudeSea => 1,
+            UnplausibleSignal::SpeedAir => 2,
+            UnplausibleSignal::SpeedGround => 3,
+            UnplausibleSignal::Anachronistic => 4,
+            UnplausibleSignal::Stale => 5,
+        }
+    }
+}
+
+/// Set of the implausible signals found in one `AircraftState`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InputFaults {
+    faults: [Option<UnplausibleSignal>; UnplausibleSignal::COUNT],
+}
+
+impl InputFaults {
+    /// Adds a fault, adding the same fault twice has no effect
+    pub fn insert(&mut self, fault: UnplausibleSignal) {
+        self.faults[fault.index()] = Some(fault);
+    }
+
+    /// Whether a specific fault was found
+    pub fn contains(&self, fault: UnplausibleSignal) -> bool {
+        self.faults[fault.index()].is_some()
+    }
+
+    /// `true` if the aircraft state is plausible
+    pub fn is_empty(&self) -> bool {
+        self.faults.iter().all(Option::is_none)
+    }
+
+    /// Whether any of the faults prevents an alert system from processing the aircraft state
+    pub fn affect(&self, alert: Alert) -> bool {
+        self.iter().any(|fault| fault.affects(alert))
+    }
+
+    /// Get an iterator to the faults
+    pub fn iter(&self) -> impl Iterator<Item = UnplausibleSignal> + '_ {
+        self.faults.iter().flatten().copied()
+    }
 }
 
-#[derive(Debug)]
+/// Checks each `AircraftState` against itself and against the last plausible one
+///
+/// States with faults are not remembered, so the next state is compared against the last
+/// plausible state again. Only a state whose timestamp does not increase, or increases too much,
+/// replaces it nevertheless, as the timestamps of the source may have been reset, e.g. by a reboot,
+/// or the source may have been interrupted, and would otherwise never be plausible again.
+#[derive(Clone, Debug, Default)]
 pub struct SignalTest {
-    states: AllocRingBuffer<AircraftState>,
+    last_state: Option<AircraftState>,
 }
 
 impl SignalTest {
-    pub fn new(config: &TAWSConfig) -> Self {
-        Self {
-            states: AllocRingBuffer::with_capacity(16),
+    /// Highest plausible climb or descent rate in feet per minute
+    const MAX_CLIMB_RATE: f64 = 20000.0;
+
+    /// Highest plausible change of airspeed or ground speed in knots per second
+    const MAX_ACCELERATION: f64 = 20.0;
+
+    /// Longest time in seconds between two aircraft states before the input is considered stale
+    const MAX_INTERVAL: f64 = 2.0;
+
+    /// Checks a new `AircraftState`, returns all implausibilities which were found
+    pub fn check(&mut self, state: &AircraftState) -> InputFaults {
+        let mut faults = InputFaults::default();
+
+        if state.altitude_ground < Length::new::<foot>(0.0) {
+            faults.insert(UnplausibleSignal::AltitudeGround);
         }
-    }
 
-    pub fn check(
-        &mut self,
-        aircraft_state: AircraftState,
-    ) -> Result<AircraftState, UnplausibleSignal> {
-        // push new aircraft states
-        self.states.push(aircraft_state);
+        if let Some(last_state) = &self.last_state {
+            let dt = state.timestamp - last_state.timestamp;
+            if dt <= Time::new::<second>(0.0) {
+                // changes over time can not be judged without time passing
+                faults.insert(UnplausibleSignal::Anachronistic);
+                self.last_state = Some(state.clone());
+                return faults;
+            }
+            if dt > Time::new::<second>(Self::MAX_INTERVAL) {
+                // the state of the aircraft may have changed arbitrarily in between
+                faults.insert(UnplausibleSignal::Stale);
+                self.last_state = Some(state.clone());
+                return faults;
+            }
+
+            let max_altitude_change = Velocity::new::<foot_per_minute>(Self::MAX_CLIMB_RATE) * dt;
+            if (state.altitude - last_state.altitude).abs() > max_altitude_change {
+                faults.insert(UnplausibleSignal::AltitudeSea);
+            }
+
+            let max_speed_change =
+                Velocity::new::<knot>(Self::MAX_ACCELERATION * dt.get::<second>());
+            if (state.speed_air - last_state.speed_air).abs() > max_speed_change {
+                faults.insert(UnplausibleSignal::SpeedAir);
+            }
+            if (state.speed_ground - last_state.speed_ground).abs() > max_speed_change {
+                faults.insert(UnplausibleSignal::SpeedGround);
+            }
+        }
 
-        // check if enough aircraft states where gathered
-        if self.states.len() < self.states.capacity() {
-            return Err(UnplausibleSignal::NotEnoughData);
+        if faults.is_empty() {
+            self.last_state = Some(state.clone());
         }
+        faults
+    }
+}
 
-        // check if the new aircraft state goes forward in time
-        let dt = self.states.get(-2).unwrap().timestamp - self.states.get(-1).unwrap().timestamp;
-        if Time::new::<second>(0.0) >= dt {
-            return Err(UnplausibleSignal::Anachronistic);
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn state(time: f64, altitude: f64, speed: f64) -> AircraftState {
+        AircraftState {
+            timestamp: Time::new::<second>(time),
+            altitude: Length::new::<foot>(altitude),
+            altitude_ground: Length::new::<foot>(altitude),
+            speed_air: Velocity::new::<knot>(speed),
+            speed_ground: Velocity::new::<knot>(speed),
+            ..Default::default()
         }
+    }
 
-        let last_state = self.states.get(-2).unwrap();
-        let current_state = self.states.get(-1).unwrap();
+    #[test]
+    fn plausible_states() {
+        let mut signal_test = SignalTest::default();
 
-        // check if altitude ground is negative
-        if current_state.altitude_ground < Length::new::<foot>(0.0) {
-            return Err(UnplausibleSignal::AltitudeGround);
+        assert!(signal_test.check(&state(0.0, 1000.0, 140.0)).is_empty());
+        assert!(signal_test.check(&state(1.0, 1050.0, 145.0)).is_empty());
+        assert!(signal_test.check(&state(2.0, 1000.0, 140.0)).is_empty());
+    }
+
+    fn faults(faults: &[UnplausibleSignal]) -> InputFaults {
+        let mut input_faults = InputFaults::default();
+        for fault in faults {
+            input_faults.insert(*fault);
         }
+        input_faults
+    }
+
+    #[test]
+    fn faults_affect_alert_systems_using_the_signal() {
+        let input_faults = faults(&[UnplausibleSignal::SpeedGround]);
+
+        assert!(input_faults.affect(Alert::Flta));
+        assert!(!input_faults.affect(Alert::Mode1));
+        assert!(faults(&[UnplausibleSignal::Anachronistic]).affect(Alert::Mode1));
+    }
+
+    #[test]
+    fn negative_height_above_terrain() {
+        let mut signal_test = SignalTest::default();
+        let mut below_terrain = state(0.0, 1000.0, 140.0);
+        below_terrain.altitude_ground = Length::new::<foot>(-10.0);
+
+        assert_eq!(
+            signal_test.check(&below_terrain),
+            faults(&[UnplausibleSignal::AltitudeGround])
+        );
+    }
+
+    #[test]
+    fn timestamps_must_increase() {
+        let mut signal_test = SignalTest::default();
+
+        assert!(signal_test.check(&state(1.0, 1000.0, 140.0)).is_empty());
+        assert_eq!(
+            signal_test.check(&state(1.0, 1000.0, 140.0)),
+            faults(&[UnplausibleSignal::Anachronistic])
+        );
+        assert_eq!(
+            signal_test.check(&state(0.5, 1000.0, 140.0)),
+            faults(&[UnplausibleSignal::Anachronistic])
+        );
+    }
+
+    #[test]
+    fn gaps_are_faults() {
+        let mut signal_test = SignalTest::default();
+
+        assert!(signal_test.check(&state(0.0, 1000.0, 140.0)).is_empty());
+        assert_eq!(
+            signal_test.check(&state(3.0, 1000.0, 140.0)),
+            faults(&[UnplausibleSignal::Stale])
+        );
+
+        // the state after the gap is the reference again, even with a jump
+        assert!(signal_test.check(&state(4.0, 1050.0, 145.0)).is_empty());
+        assert_eq!(
+            signal_test.check(&state(10.0, 5000.0, 140.0)),
+            faults(&[UnplausibleSignal::Stale])
+        );
+    }
+
+    #[test]
+    fn jumps_are_faults() {
+        let mut signal_test = SignalTest::default();
+
+        assert!(signal_test.check(&state(0.0, 1000.0, 140.0)).is_empty());
+        assert_eq!(
+            signal_test.check(&state(1.0, 2000.0, 140.0)),
+            faults(&[UnplausibleSignal::AltitudeSea])
+        );
+        assert_eq!(
+            signal_test.check(&state(1.0, 2000.0, 200.0)),
+            faults(&[
+                UnplausibleSignal::AltitudeSea,
+                UnplausibleSignal::SpeedAir,
+                UnplausibleSignal::SpeedGround
+            ])
+        );
+
+        // the states with faults are not used as reference
+        assert!(signal_test.check(&state(2.0, 1000.0, 140.0)).is_empty());
+    }
 
-        // check if height change is plausible
-        let max_speed = last_state.speed_ground.max(current_state.speed_ground);
-        let d_altitude = current_state.altitude - last_state.altitude;
-        //if d_altitude > Ratio::new::<ratio>(1.5) * dt * max_speed  {
-        //    return Err(UnplausibleSignal::AltitudeSea);
-        //}
+    #[test]
+    fn recovery_after_timestamp_reset() {
+        let mut signal_test = SignalTest::default();
 
-        Ok(self.states.get(-1).unwrap().clone())
+        assert!(signal_test.check(&state(100.0, 1000.0, 140.0)).is_empty());
+        assert_eq!(
+            signal_test.check(&state(0.0, 1000.0, 140.0)),
+            faults(&[UnplausibleSignal::Anachronistic])
+        );
+        assert!(signal_test.check(&state(1.0, 1000.0, 140.0)).is_empty());
+        assert!(signal_test.check(&state(2.0, 1000.0, 140.0)).is_empty());
     }
 }
@@ -0,0 +1,212 @@
+//! The outcome of processing one `AircraftState`, including what the TAWS could not do
+
+use crate::alerts::ALERT_STATE_SIZE;
+use crate::prelude::*;
+
+/// Availability of one alert system while processing an `AircraftState`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FunctionStatus {
+    /// The alert system is armed
+    pub armed: bool,
+
+    /// The alert system is inhibited, e.g. by the crew
+    pub inhibited: bool,
+
+    /// The alert system lost a data source it depends on, e.g. the terrain database, or the
+    /// self-test found a failure affecting it
+    pub failed: bool,
+
+    /// The alert system did not process the `AircraftState` due to an input fault
+    pub skipped: bool,
+}
+
+impl FunctionStatus {
+    /// `true` if the alert system could not protect the aircraft, regardless of the crew's choice
+    pub fn is_unavailable(&self) -> bool {
+        self.failed || self.skipped
+    }
+}
+
+/// Everything the TAWS found when processing an `AircraftState`
+#[derive(Debug, Default, PartialEq)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TawsState {
+    alert_state: AlertState,
+    functions: [Option<(Alert, FunctionStatus)>; ALERT_STATE_SIZE],
+    events: AlertEvents,
+    voice_message: Option<VoiceMessage>,
+    silenced_voice_message: Option<VoiceMessage>,
+    timings: [Option<(Alert, AlertTiming)>; ALERT_STATE_SIZE],
+    input_faults: InputFaults,
+    self_test_failures: SelfTestFailures,
+}
+
+impl TawsState {
+    pub(crate) fn new(
+        priorities: AlertPriorities,
+        input_faults: InputFaults,
+        self_test_failures: SelfTestFailures,
+    ) -> Self {
+        Self {
+            alert_state: AlertState::new(priorities),
+            input_faults,
+            self_test_failures,
+            ..Default::default()
+        }
+    }
+
+    /// The alerts which are present
+    ///
+    /// Alerts are held for a minimum time after their onset and clear only once their condition
+    /// is gone for a while, see `TawsConfig::alert_min_duration` and
+    /// `TawsConfig::alert_clear_delay`.
+    pub fn alert_state(&self) -> &AlertState {
+        &self.alert_state
+    }
+
+    pub(crate) fn alert_state_mut(&mut self) -> &mut AlertState {
+        &mut self.alert_state
+    }
+
+    /// Replaces the alerts emitted by the alert systems with those to present to the crew
+    pub(crate) fn set_alert_state(
+        &mut self,
+        alert_state: AlertState,
+        events: AlertEvents,
+        timings: impl Iterator<Item = (Alert, AlertTiming)>,
+    ) {
+        self.alert_state = alert_state;
+        self.events = events;
+        for (option, timing) in self.timings.iter_mut().zip(timings) {
+            *option = Some(timing);
+        }
+    }
+
+    /// The changes of the alerts since the last `AircraftState`, so that they need not be derived
+    /// by comparing successive `AlertState`s
+    pub fn events(&self) -> &AlertEvents {
+        &self.events
+    }
+
+    /// The voice message to start playing, `None` if no new voice message is due
+    ///
+    /// A voice message which is still playing must be stopped in favour of this one, as only a
+    /// warning of higher priority interrupts another voice message.
+    pub fn voice_message(&self) -> Option<VoiceMessage> {
+        self.voice_message
+    }
+
+    /// The voice message to stop immediately, as an external alert of higher priority became
+    /// active while it was played
+    pub fn silenced_voice_message(&self) -> Option<VoiceMessage> {
+        self.silenced_voice_message
+    }
+
+    pub(crate) fn set_voice_message(
+        &mut self,
+        voice_message: Option<VoiceMessage>,
+        silenced_voice_message: Option<VoiceMessage>,
+    ) {
+        self.voice_message = voice_message;
+        self.silenced_voice_message = silenced_voice_message;
+    }
+
+    /// Get the onset and the clearing of an alert, `None` if it never occurred
+    pub fn timing(&self, alert: Alert) -> Option<AlertTiming> {
+        self.timings()
+            .find(|(existing_alert, _)| *existing_alert == alert)
+            .map(|(_, timing)| timing)
+    }
+
+    /// Get an iterator to the onset and the clearing of all alerts which occurred so far
+    pub fn timings(&self) -> impl Iterator<Item = (Alert, AlertTiming)> + '_ {
+        self.timings.iter().flatten().copied()
+    }
+
+    /// The implausible signals found in the `AircraftState`
+    pub fn input_faults(&self) -> &InputFaults {
+        &self.input_faults
+    }
+
+    /// The failures found by the continuous and the initiated self-test
+    pub fn self_test_failures(&self) -> &SelfTestFailures {
+        &self.self_test_failures
+    }
+
+    /// Get the availability of an alert system
+    pub fn function(&self, alert: Alert) -> FunctionStatus {
+        self.functions()
+            .find(|(existing_alert, _)| *existing_alert == alert)
+            .map(|(_, status)| status)
+            .unwrap_or_default()
+    }
+
+    /// Get an iterator to the availability of all alert systems
+    pub fn functions(&self) -> impl Iterator<Item = (Alert, FunctionStatus)> + '_ {
+        self.functions.iter().flatten().copied()
+    }
+
+    /// Get an iterator to the alert systems which did not process the `AircraftState` due to an
+    /// input fault
+    pub fn skipped(&self) -> impl Iterator<Item = Alert> + '_ {
+        self.functions()
+            .filter(|(_, status)| status.skipped)
+            .map(|(alert, _)| alert)
+    }
+
+    /// `true` if the terrain awareness (FLTA or PDA) is unavailable, annunciated as "TERR FAIL"
+    pub fn terrain_failed(&self) -> bool {
+        [Alert::Flta, Alert::Pda]
+            .iter()
+            .any(|alert| self.function(*alert).is_unavailable())
+    }
+
+    /// `true` if any of the basic GPWS modes is unavailable, annunciated as "TAWS INOP"
+    pub fn taws_inoperative(&self) -> bool {
+        use Alert::*;
+
+        [Mode1, Mode2, Mode3, Mode4, Mode5]
+            .iter()
+            .any(|alert| self.function(*alert).is_unavailable())
+    }
+
+    pub(crate) fn insert_function(&mut self, alert: Alert, status: FunctionStatus) {
+        if let Some(option) = self.functions.iter_mut().find(|e| e.is_none()) {
+            *option = Some((alert, status));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn unknown_functions_have_default_status() {
+        let taws_state = TawsState::default();
+
+        assert_eq!(taws_state.function(Alert::Mode1), FunctionStatus::default());
+        assert!(!taws_state.terrain_failed());
+        assert!(!taws_state.taws_inoperative());
+    }
+
+    #[test]
+    fn skipped_basic_mode_makes_taws_inoperative() {
+        let mut taws_state = TawsState::default();
+        let skipped = FunctionStatus {
+            armed: true,
+            skipped: true,
+            ..Default::default()
+        };
+        taws_state.insert_function(Alert::Flta, FunctionStatus::default());
+        taws_state.insert_function(Alert::Mode2, skipped);
+
+        assert!(!taws_state.terrain_failed());
+        assert!(taws_state.taws_inoperative());
+        assert_eq!(
+            taws_state.skipped().collect::<std::vec::Vec<_>>(),
+            [Alert::Mode2]
+        );
+    }
+}
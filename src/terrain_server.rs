@@ -1,32 +1,292 @@
-use core::convert::{From,Into};
+use core::convert::From;
+use core::fmt;
+
+use uom::si::{angle::radian, length::kilometer};
 
 use crate::prelude::*;
 
+#[cfg(feature = "dted")]
+pub mod dted;
+pub mod runway_database;
+#[cfg(feature = "srtm")]
+pub mod srtm;
+
+/// Provides the elevation of the terrain
+pub trait TerrainServer: fmt::Debug + Sync {
+    /// Get the terrain elevation above sea level at a position
+    fn elevation(&self, position: &Position) -> Result<Length, TerrainError>;
+}
+
+/// Reasons why a `TerrainServer` can not provide the elevation of a position
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TerrainError {
+    /// The terrain data does not cover the position, or contains a void at the position
+    NoData,
+
+    /// The terrain data could not be accessed
+    Unavailable,
+}
+
+impl fmt::Display for TerrainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TerrainError::NoData => write!(f, "no terrain data available for this position"),
+            TerrainError::Unavailable => write!(f, "terrain data unavailable"),
+        }
+    }
+}
 
-pub trait TerrainServer {
-    fn elevation<T: Into<Position>>(&self, position:T)->Length;
-    fn nearest_runway<T: Into<Position>>(&self, position:T)->Runway;
+/// Provides the runways which are known to the TAWS
+pub trait RunwayServer: fmt::Debug + Sync {
+    /// Get the runway which is closest to a position, `None` if no runway is known
+    ///
+    /// Fails with `TerrainError::Unavailable` if the runway data can not be accessed.
+    fn nearest_runway(&self, position: &Position) -> Result<Option<Runway>, TerrainError>;
 }
 
+/// A position on or above the earth
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Position {
-    latitude: Angle,
-    longitude: Angle,
-    altitude_sea: Length,
+    /// Geographic Latitude, specifying the north-south position
+    pub latitude: Angle,
+
+    /// Geographic Longitude, specifying the east-west position
+    pub longitude: Angle,
+
+    /// Height above sea level
+    pub altitude_sea: Length,
+}
+
+impl Position {
+    /// Great circle distance between two positions, ignoring their altitudes
+    pub fn distance(&self, other: &Position) -> Length {
+        let earth_radius = Self::earth_radius();
+
+        let (lat1, lon1) = (
+            self.latitude.get::<radian>(),
+            self.longitude.get::<radian>(),
+        );
+        let (lat2, lon2) = (
+            other.latitude.get::<radian>(),
+            other.longitude.get::<radian>(),
+        );
+
+        // haversine formula
+        let a = libm::pow(libm::sin((lat2 - lat1) / 2.0), 2.0)
+            + libm::cos(lat1) * libm::cos(lat2) * libm::pow(libm::sin((lon2 - lon1) / 2.0), 2.0);
+        let central_angle = 2.0 * libm::asin(libm::sqrt(a).min(1.0));
+
+        earth_radius * central_angle
+    }
+
+    /// Initial angle (clockwise) between north and the great circle from this position to another
+    pub fn bearing(&self, other: &Position) -> Angle {
+        let (lat1, lon1) = (
+            self.latitude.get::<radian>(),
+            self.longitude.get::<radian>(),
+        );
+        let (lat2, lon2) = (
+            other.latitude.get::<radian>(),
+            other.longitude.get::<radian>(),
+        );
+
+        let bearing = libm::atan2(
+            libm::sin(lon2 - lon1) * libm::cos(lat2),
+            libm::cos(lat1) * libm::sin(lat2)
+                - libm::sin(lat1) * libm::cos(lat2) * libm::cos(lon2 - lon1),
+        );
+
+        let bearing = if bearing < 0.0 {
+            bearing + 2.0 * core::f64::consts::PI
+        } else {
+            bearing
+        };
+
+        Angle::new::<radian>(bearing)
+    }
+
+    /// The position reached when travelling along a great circle from this position, keeping the
+    /// altitude
+    ///
+    /// # Arguments
+    ///
+    /// * `bearing` - Initial angle (clockwise) between north and the direction of travel
+    /// * `distance` - Distance to travel
+    pub fn destination(&self, bearing: Angle, distance: Length) -> Position {
+        let central_angle = (distance / Self::earth_radius()).value;
+        let bearing = bearing.get::<radian>();
+        let (lat1, lon1) = (
+            self.latitude.get::<radian>(),
+            self.longitude.get::<radian>(),
+        );
+
+        let lat2 = libm::asin(
+            libm::sin(lat1) * libm::cos(central_angle)
+                + libm::cos(lat1) * libm::sin(central_angle) * libm::cos(bearing),
+        );
+        let lon2 = lon1
+            + libm::atan2(
+                libm::sin(bearing) * libm::sin(central_angle) * libm::cos(lat1),
+                libm::cos(central_angle) - libm::sin(lat1) * libm::sin(lat2),
+            );
+
+        Position {
+            latitude: Angle::new::<radian>(lat2),
+            longitude: Angle::new::<radian>(lon2),
+            altitude_sea: self.altitude_sea,
+        }
+    }
+
+    fn earth_radius() -> Length {
+        Length::new::<kilometer>(6371.0)
+    }
 }
 
 impl From<&AircraftState> for Position {
-    fn from(aircraft_state: AircraftState)->Self{
-        Position{
-            longitude: aircraft_state.longitude,
-            latitude: aircraft_state.latitude,
-            altitude_sea: aircraft_state.altitude_sea,
+    fn from(aircraft_state: &AircraftState) -> Self {
+        Position {
+            latitude: aircraft_state.position_lat,
+            longitude: aircraft_state.position_lon,
+            altitude_sea: aircraft_state.altitude,
         }
     }
 }
 
+/// A runway, described by its threshold
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Runway {
-    location: Position,
-    length: Length,
-    name: String,
-    azimuth: Angle,
-}
\ No newline at end of file
+    /// Identifier of the airport the runway belongs to, e.g. `EDDH`
+    pub airport: Identifier,
+
+    /// Designator of the runway, e.g. `05`, `23L`
+    pub designator: Identifier,
+
+    /// Position and elevation of the runway threshold
+    pub location: Position,
+
+    /// Length of the runway
+    pub length: Length,
+
+    /// Angle in degrees (clockwise) between north and the runway direction
+    pub azimuth: Angle,
+}
+
+/// An ASCII identifier with a fixed capacity of `Identifier::CAPACITY` characters
+///
+/// `String` is not available in `no_std`, so identifiers are stored inline.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Identifier {
+    bytes: [u8; Identifier::CAPACITY],
+    len: u8,
+}
+
+impl Identifier {
+    /// Maximum number of characters of an identifier
+    pub const CAPACITY: usize = 8;
+
+    /// Create a new identifier, `None` if `identifier` is not ASCII or longer than
+    /// `Identifier::CAPACITY`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use opentaws::terrain_server::Identifier;
+    ///
+    /// assert_eq!(Identifier::new("23L").unwrap().as_str(), "23L");
+    /// assert!(Identifier::new("TOO LONG ID").is_none());
+    /// ```
+    pub fn new(identifier: &str) -> Option<Self> {
+        if !identifier.is_ascii() || identifier.len() > Self::CAPACITY {
+            return None;
+        }
+
+        let mut bytes = [0; Self::CAPACITY];
+        bytes[..identifier.len()].copy_from_slice(identifier.as_bytes());
+
+        Some(Self {
+            bytes,
+            len: identifier.len() as u8,
+        })
+    }
+
+    /// The identifier as string slice
+    pub fn as_str(&self) -> &str {
+        // only ASCII is ever stored, so this can not fail
+        core::str::from_utf8(&self.bytes[..self.len as usize]).unwrap_or_default()
+    }
+}
+
+impl fmt::Debug for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "use-serde")]
+impl serde::Serialize for Identifier {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "use-serde")]
+impl<'de> serde::Deserialize<'de> for Identifier {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct IdentifierVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for IdentifierVisitor {
+            type Value = Identifier;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "an ASCII string of at most {} bytes",
+                    Identifier::CAPACITY
+                )
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Identifier::new(v)
+                    .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Str(v), &self))
+            }
+        }
+
+        deserializer.deserialize_str(IdentifierVisitor)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use uom::si::length::meter;
+
+    use super::*;
+
+    /// Runway database with a single runway at sea level, its threshold at null island and heading
+    /// north
+    #[derive(Debug)]
+    pub(crate) struct NullIsland;
+
+    impl RunwayServer for NullIsland {
+        fn nearest_runway(&self, _position: &Position) -> Result<Option<Runway>, TerrainError> {
+            Ok(Some(Runway {
+                airport: Identifier::new("NULL").unwrap(),
+                designator: Identifier::new("36").unwrap(),
+                location: Position {
+                    latitude: Angle::new::<degree>(0.0),
+                    longitude: Angle::new::<degree>(0.0),
+                    altitude_sea: Length::new::<foot>(0.0),
+                },
+                length: Length::new::<meter>(3000.0),
+                azimuth: Angle::new::<degree>(0.0),
+            }))
+        }
+    }
+}
@@ -0,0 +1,499 @@
+//! Reader for Digital Terrain Elevation Data (DTED) Level 0, 1 and 2 as specified in
+//! MIL-PRF-89020B
+//!
+//! A DTED file contains one tile, which starts with the User Header Label (UHL), the Data Set
+//! Identification (DSI) record and the Accuracy Description (ACC) record. These are followed by
+//! one data record per longitude line, from west to east. Each data record holds the elevations of
+//! its longitude line from south to north.
+
+use std::{
+    error, fmt, fs,
+    io::{self, Read},
+    path::Path,
+    vec::Vec,
+};
+
+use uom::si::length::meter;
+
+use crate::prelude::*;
+
+const UHL_LENGTH: usize = 80;
+const DSI_LENGTH: usize = 648;
+const ACC_LENGTH: usize = 2700;
+const HEADER_LENGTH: usize = UHL_LENGTH + DSI_LENGTH + ACC_LENGTH;
+
+/// Sentinel which starts every data record
+const RECORD_SENTINEL: u8 = 0xAA;
+
+/// Bytes of a data record in front of the elevations
+const RECORD_PREFIX_LENGTH: usize = 8;
+
+/// Bytes of the checksum at the end of a data record
+const RECORD_CHECKSUM_LENGTH: usize = 4;
+
+/// Elevation which marks a void in the data
+const VOID: i16 = -32767;
+
+/// Product level of a DTED tile, which determines its resolution
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DtedLevel {
+    /// Post spacing of 30 arc seconds
+    Level0,
+
+    /// Post spacing of 3 arc seconds
+    Level1,
+
+    /// Post spacing of 1 arc second
+    Level2,
+}
+
+/// Errors which can occur while reading DTED
+#[derive(Debug)]
+pub enum DtedError {
+    /// The file could not be read
+    Io(io::Error),
+
+    /// A header record is missing or contains an invalid field
+    InvalidHeader(&'static str),
+
+    /// The data record of a longitude line is truncated or malformed
+    InvalidRecord { line: usize },
+
+    /// The checksum of the data record of a longitude line does not match its content
+    ChecksumMismatch { line: usize },
+}
+
+impl fmt::Display for DtedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DtedError::Io(error) => write!(f, "unable to read DTED: {}", error),
+            DtedError::InvalidHeader(field) => write!(f, "invalid DTED header: {}", field),
+            DtedError::InvalidRecord { line } => {
+                write!(f, "invalid DTED data record for longitude line {}", line)
+            }
+            DtedError::ChecksumMismatch { line } => {
+                write!(f, "checksum mismatch for longitude line {}", line)
+            }
+        }
+    }
+}
+
+impl error::Error for DtedError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            DtedError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DtedError {
+    fn from(error: io::Error) -> Self {
+        DtedError::Io(error)
+    }
+}
+
+/// A single DTED tile
+#[derive(Clone, Debug, PartialEq)]
+pub struct DtedTile {
+    level: DtedLevel,
+
+    /// Latitude and longitude in degrees of the south west corner
+    origin: (f64, f64),
+
+    /// Spacing between two latitude points and two longitude lines in degrees
+    interval: (f64, f64),
+
+    latitude_points: usize,
+    longitude_lines: usize,
+
+    /// Elevations in meters, one longitude line after another
+    elevations: Vec<i16>,
+}
+
+impl DtedTile {
+    /// Read a tile from a DTED file
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, DtedError> {
+        Self::from_reader(fs::File::open(path)?)
+    }
+
+    /// Read a tile from a reader providing the content of a DTED file
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self, DtedError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Parse a tile from the content of a DTED file
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DtedError> {
+        if bytes.len() < HEADER_LENGTH {
+            return Err(DtedError::InvalidHeader("file too short"));
+        }
+
+        let uhl = &bytes[..UHL_LENGTH];
+        let dsi = &bytes[UHL_LENGTH..UHL_LENGTH + DSI_LENGTH];
+        let acc = &bytes[UHL_LENGTH + DSI_LENGTH..HEADER_LENGTH];
+
+        if !uhl.starts_with(b"UHL") {
+            return Err(DtedError::InvalidHeader("missing UHL record"));
+        }
+        if !dsi.starts_with(b"DSI") {
+            return Err(DtedError::InvalidHeader("missing DSI record"));
+        }
+        if !acc.starts_with(b"ACC") {
+            return Err(DtedError::InvalidHeader("missing ACC record"));
+        }
+
+        let level = match &dsi[59..64] {
+            b"DTED0" => DtedLevel::Level0,
+            b"DTED1" => DtedLevel::Level1,
+            b"DTED2" => DtedLevel::Level2,
+            _ => return Err(DtedError::InvalidHeader("unknown DTED level")),
+        };
+
+        let origin_longitude =
+            parse_angle(&uhl[4..12]).ok_or(DtedError::InvalidHeader("origin longitude"))?;
+        let origin_latitude =
+            parse_angle(&uhl[12..20]).ok_or(DtedError::InvalidHeader("origin latitude"))?;
+
+        // intervals are given in tenths of arc seconds
+        let longitude_interval = parse_number(&uhl[20..24])
+            .filter(|interval| *interval > 0)
+            .ok_or(DtedError::InvalidHeader("longitude interval"))?
+            as f64
+            / 36_000.0;
+        let latitude_interval = parse_number(&uhl[24..28])
+            .filter(|interval| *interval > 0)
+            .ok_or(DtedError::InvalidHeader("latitude interval"))?
+            as f64
+            / 36_000.0;
+
+        let longitude_lines = parse_number(&uhl[47..51])
+            .filter(|lines| *lines >= 2)
+            .ok_or(DtedError::InvalidHeader("number of longitude lines"))?;
+        let latitude_points = parse_number(&uhl[51..55])
+            .filter(|points| *points >= 2)
+            .ok_or(DtedError::InvalidHeader("number of latitude points"))?;
+
+        let record_length = RECORD_PREFIX_LENGTH + 2 * latitude_points + RECORD_CHECKSUM_LENGTH;
+        let mut elevations = Vec::with_capacity(longitude_lines * latitude_points);
+
+        for line in 0..longitude_lines {
+            let start = HEADER_LENGTH + line * record_length;
+            let record = bytes
+                .get(start..start + record_length)
+                .ok_or(DtedError::InvalidRecord { line })?;
+
+            if record[0] != RECORD_SENTINEL {
+                return Err(DtedError::InvalidRecord { line });
+            }
+
+            let (content, checksum) = record.split_at(record_length - RECORD_CHECKSUM_LENGTH);
+            let expected = u32::from_be_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]);
+            let actual = content
+                .iter()
+                .fold(0u32, |sum, byte| sum.wrapping_add(u32::from(*byte)));
+            if actual != expected {
+                return Err(DtedError::ChecksumMismatch { line });
+            }
+
+            // elevations are stored as signed magnitude, not as two's complement
+            elevations.extend(content[RECORD_PREFIX_LENGTH..].chunks_exact(2).map(|raw| {
+                let raw = u16::from_be_bytes([raw[0], raw[1]]);
+                let magnitude = (raw & 0x7fff) as i16;
+                if raw & 0x8000 != 0 {
+                    -magnitude
+                } else {
+                    magnitude
+                }
+            }));
+        }
+
+        Ok(Self {
+            level,
+            origin: (origin_latitude, origin_longitude),
+            interval: (latitude_interval, longitude_interval),
+            latitude_points,
+            longitude_lines,
+            elevations,
+        })
+    }
+
+    /// The product level of this tile
+    pub fn level(&self) -> DtedLevel {
+        self.level
+    }
+
+    /// Returns `true` if the position lies within the area covered by this tile
+    pub fn contains(&self, position: &Position) -> bool {
+        self.grid_coordinates(position).is_some()
+    }
+
+    /// Bilinear interpolation of the elevation at a position
+    ///
+    /// Returns `TerrainError::NoData` if the position is not covered by this tile or if one of the
+    /// surrounding posts is a void.
+    pub fn elevation(&self, position: &Position) -> Result<Length, TerrainError> {
+        let (x, y) = self
+            .grid_coordinates(position)
+            .ok_or(TerrainError::NoData)?;
+
+        // the upper most line and point are interpolated from the cell below them
+        let line = (x as usize).min(self.longitude_lines - 2);
+        let point = (y as usize).min(self.latitude_points - 2);
+        let (dx, dy) = (x - line as f64, y - point as f64);
+
+        let post = |line: usize, point: usize| match self.elevations
+            [line * self.latitude_points + point]
+        {
+            VOID => Err(TerrainError::NoData),
+            elevation => Ok(f64::from(elevation)),
+        };
+
+        let south = post(line, point)? * (1.0 - dx) + post(line + 1, point)? * dx;
+        let north = post(line, point + 1)? * (1.0 - dx) + post(line + 1, point + 1)? * dx;
+
+        Ok(Length::new::<meter>(south * (1.0 - dy) + north * dy))
+    }
+
+    /// Position in units of longitude lines and latitude points from the origin, `None` if it lies
+    /// outside of this tile
+    fn grid_coordinates(&self, position: &Position) -> Option<(f64, f64)> {
+        let x = (position.longitude.get::<degree>() - self.origin.1) / self.interval.1;
+        let y = (position.latitude.get::<degree>() - self.origin.0) / self.interval.0;
+
+        let inside = (0.0..=(self.longitude_lines - 1) as f64).contains(&x)
+            && (0.0..=(self.latitude_points - 1) as f64).contains(&y);
+
+        inside.then_some((x, y))
+    }
+}
+
+/// Terrain source backed by a set of DTED tiles
+///
+/// # Example
+///
+/// ```no_run
+/// use opentaws::prelude::*;
+/// use opentaws::terrain_server::dted::DtedTerrain;
+///
+/// let terrain = DtedTerrain::load_dir("/usr/share/dted").unwrap();
+/// let config = TawsConfig {
+///     terrain_server: Some(&terrain),
+///     ..Default::default()
+/// };
+/// let taws = Taws::new(config).unwrap();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DtedTerrain {
+    tiles: Vec<DtedTile>,
+}
+
+impl DtedTerrain {
+    /// Create a terrain source without any tiles
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load all DTED files (`.dt0`, `.dt1` and `.dt2`) from a directory and its subdirectories
+    pub fn load_dir<P: AsRef<Path>>(path: P) -> Result<Self, DtedError> {
+        let mut terrain = Self::new();
+        terrain.insert_dir(path.as_ref())?;
+        Ok(terrain)
+    }
+
+    /// Add a tile
+    ///
+    /// Where tiles overlap, the tile which was added first is used.
+    pub fn insert(&mut self, tile: DtedTile) {
+        self.tiles.push(tile);
+    }
+
+    fn insert_dir(&mut self, path: &Path) -> Result<(), DtedError> {
+        let mut entries = fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        entries.sort();
+
+        for entry in entries {
+            if entry.is_dir() {
+                self.insert_dir(&entry)?;
+                continue;
+            }
+
+            let extension = entry.extension().and_then(|extension| extension.to_str());
+            if let Some("dt0" | "dt1" | "dt2") = extension {
+                self.insert(DtedTile::open(&entry)?);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl TerrainServer for DtedTerrain {
+    fn elevation(&self, position: &Position) -> Result<Length, TerrainError> {
+        self.tiles
+            .iter()
+            .find(|tile| tile.contains(position))
+            .ok_or(TerrainError::NoData)?
+            .elevation(position)
+    }
+}
+
+/// Parses an angle in the format `DDDMMSSH`, with `H` being the hemisphere
+fn parse_angle(field: &[u8]) -> Option<f64> {
+    let degrees = parse_number(&field[0..3])? as f64;
+    let minutes = parse_number(&field[3..5])? as f64;
+    let seconds = parse_number(&field[5..7])? as f64;
+    let angle = degrees + minutes / 60.0 + seconds / 3600.0;
+
+    match field[7] {
+        b'N' | b'E' => Some(angle),
+        b'S' | b'W' => Some(-angle),
+        _ => None,
+    }
+}
+
+/// Parses a fixed width field of ASCII digits
+fn parse_number(field: &[u8]) -> Option<usize> {
+    core::str::from_utf8(field).ok()?.parse().ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/dted")
+            .join(name)
+    }
+
+    fn position(latitude: f64, longitude: f64) -> Position {
+        Position {
+            latitude: Angle::new::<degree>(latitude),
+            longitude: Angle::new::<degree>(longitude),
+            altitude_sea: Length::new::<meter>(0.0),
+        }
+    }
+
+    fn assert_elevation(actual: Result<Length, TerrainError>, expected: f64) {
+        let actual = actual.unwrap().get::<meter>();
+        assert!(
+            (actual - expected).abs() < 1e-6,
+            "expected {} m, got {} m",
+            expected,
+            actual
+        );
+    }
+
+    /// Level 0 tile at N53 E010 with 5 x 5 posts, each longitude line adds 100 m and each latitude
+    /// point adds 10 m to the elevation
+    #[test]
+    fn read_level_0() {
+        let tile = DtedTile::open(fixture("e010/n53.dt0")).unwrap();
+        assert_eq!(tile.level(), DtedLevel::Level0);
+
+        let post = 30.0 / 3600.0;
+        assert_elevation(tile.elevation(&position(53.0, 10.0)), 0.0);
+        assert_elevation(tile.elevation(&position(53.0 + post, 10.0)), 10.0);
+        assert_elevation(tile.elevation(&position(53.0, 10.0 + post)), 100.0);
+        assert_elevation(
+            tile.elevation(&position(53.0 + 4.0 * post, 10.0 + 4.0 * post)),
+            440.0,
+        );
+    }
+
+    #[test]
+    fn bilinear_interpolation() {
+        let tile = DtedTile::open(fixture("e010/n53.dt0")).unwrap();
+
+        let post = 30.0 / 3600.0;
+        assert_elevation(
+            tile.elevation(&position(53.0 + 1.5 * post, 10.0 + 2.25 * post)),
+            240.0,
+        );
+    }
+
+    #[test]
+    fn outside_of_tile() {
+        let tile = DtedTile::open(fixture("e010/n53.dt0")).unwrap();
+
+        assert_eq!(
+            tile.elevation(&position(52.99, 10.0)),
+            Err(TerrainError::NoData)
+        );
+        assert_eq!(
+            tile.elevation(&position(53.0, 10.1)),
+            Err(TerrainError::NoData)
+        );
+    }
+
+    /// Level 1 tile at N54 E010 with 4 x 4 posts at 500 m, with a void at the third line and point
+    #[test]
+    fn void_is_no_data() {
+        let tile = DtedTile::open(fixture("e010/n54.dt1")).unwrap();
+        assert_eq!(tile.level(), DtedLevel::Level1);
+
+        let post = 3.0 / 3600.0;
+        assert_elevation(tile.elevation(&position(54.0 + 0.5 * post, 10.0)), 500.0);
+        assert_eq!(
+            tile.elevation(&position(54.0 + 1.5 * post, 10.0 + 1.5 * post)),
+            Err(TerrainError::NoData)
+        );
+    }
+
+    /// Level 2 tile at S01 W001 with 3 x 3 posts below sea level
+    #[test]
+    fn southern_and_western_hemisphere() {
+        let tile = DtedTile::open(fixture("w001/s01.dt2")).unwrap();
+        assert_eq!(tile.level(), DtedLevel::Level2);
+
+        let post = 1.0 / 3600.0;
+        assert_elevation(tile.elevation(&position(-1.0, -1.0)), 0.0);
+        assert_elevation(
+            tile.elevation(&position(-1.0 + 2.0 * post, -1.0 + post)),
+            -30.0,
+        );
+    }
+
+    #[test]
+    fn checksum_mismatch() {
+        let mut bytes = fs::read(fixture("e010/n53.dt0")).unwrap();
+        bytes[HEADER_LENGTH + RECORD_PREFIX_LENGTH] ^= 0x01;
+
+        assert!(matches!(
+            DtedTile::from_bytes(&bytes),
+            Err(DtedError::ChecksumMismatch { line: 0 })
+        ));
+    }
+
+    #[test]
+    fn truncated_file() {
+        let bytes = fs::read(fixture("e010/n53.dt0")).unwrap();
+
+        assert!(matches!(
+            DtedTile::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(DtedError::InvalidRecord { line: 4 })
+        ));
+        assert!(matches!(
+            DtedTile::from_bytes(&bytes[..UHL_LENGTH]),
+            Err(DtedError::InvalidHeader(_))
+        ));
+    }
+
+    #[test]
+    fn terrain_from_directory() {
+        let terrain = DtedTerrain::load_dir(fixture("")).unwrap();
+
+        assert_elevation(terrain.elevation(&position(53.0, 10.0)), 0.0);
+        assert_elevation(terrain.elevation(&position(54.0, 10.0)), 500.0);
+        assert_elevation(terrain.elevation(&position(-1.0, -1.0)), 0.0);
+        assert_eq!(
+            terrain.elevation(&position(0.0, 0.0)),
+            Err(TerrainError::NoData)
+        );
+    }
+}
@@ -0,0 +1,879 @@
+//! Runway database with a compact binary form, which can be queried without allocation
+//!
+//! The binary form starts with the magic `OTRW` and the number of runways as little endian `u32`.
+//! It is followed by one fixed size record per runway threshold, sorted by latitude:
+//!
+//! | Bytes | Content                                                    |
+//! |-------|------------------------------------------------------------|
+//! | 8     | airport identifier, ASCII padded with zeros                |
+//! | 8     | runway designator, ASCII padded with zeros                 |
+//! | 4     | latitude in 1e-7 degrees, little endian `i32`              |
+//! | 4     | longitude in 1e-7 degrees, little endian `i32`             |
+//! | 2     | threshold elevation in foot, little endian `i16`           |
+//! | 2     | runway heading in 1/100 degrees, little endian `u16`       |
+//! | 2     | runway length in foot, little endian `u16`                 |
+//!
+//! The sorting by latitude serves as spatial index: Only runways within a latitude band around
+//! the queried position need to be checked.
+//!
+//! With the `std` feature a database can be built from the `airports.csv` and `runways.csv` files
+//! published by [OurAirports](https://ourairports.com/data/).
+
+use core::{cmp::Ordering, fmt};
+
+use uom::si::length::kilometer;
+
+use crate::prelude::*;
+
+const MAGIC: &[u8; 4] = b"OTRW";
+const HEADER_LENGTH: usize = 8;
+const RECORD_LENGTH: usize = 30;
+
+/// Scale of latitudes and longitudes in the binary form
+const ANGLE_SCALE: f64 = 1e7;
+
+/// Scale of headings in the binary form
+const HEADING_SCALE: f64 = 100.0;
+
+/// Errors which can occur while building or reading a runway database
+#[derive(Debug)]
+pub enum RunwayDatabaseError {
+    /// The binary form is malformed
+    InvalidFormat(&'static str),
+
+    /// The buffer is too small to hold the binary form
+    BufferTooSmall,
+
+    /// A CSV file could not be read
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+
+    /// A column is missing in a CSV file
+    #[cfg(feature = "std")]
+    MissingColumn(&'static str),
+
+    /// A row of a CSV file does not match its header
+    #[cfg(feature = "std")]
+    InvalidRow { line: usize },
+}
+
+impl fmt::Display for RunwayDatabaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunwayDatabaseError::InvalidFormat(reason) => {
+                write!(f, "invalid runway database: {}", reason)
+            }
+            RunwayDatabaseError::BufferTooSmall => write!(f, "buffer too small"),
+            #[cfg(feature = "std")]
+            RunwayDatabaseError::Io(error) => write!(f, "unable to read CSV: {}", error),
+            #[cfg(feature = "std")]
+            RunwayDatabaseError::MissingColumn(column) => {
+                write!(f, "missing CSV column {}", column)
+            }
+            #[cfg(feature = "std")]
+            RunwayDatabaseError::InvalidRow { line } => {
+                write!(f, "invalid CSV row in line {}", line)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RunwayDatabaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RunwayDatabaseError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for RunwayDatabaseError {
+    fn from(error: std::io::Error) -> Self {
+        RunwayDatabaseError::Io(error)
+    }
+}
+
+/// A database of runway thresholds in the binary form
+///
+/// The data can either be borrowed, e.g. from `include_bytes!`, or owned.
+///
+/// # Example
+///
+/// ```
+/// use opentaws::prelude::*;
+/// use opentaws::terrain_server::runway_database::RunwayDatabase;
+///
+/// let mut runways = [Runway {
+///     airport: Identifier::new("EDDH").unwrap(),
+///     designator: Identifier::new("23").unwrap(),
+///     location: Position {
+///         latitude: Angle::new::<degree>(53.6473),
+///         longitude: Angle::new::<degree>(10.0126),
+///         altitude_sea: Length::new::<foot>(50.0),
+///     },
+///     length: Length::new::<foot>(10663.0),
+///     azimuth: Angle::new::<degree>(233.0),
+/// }];
+///
+/// let mut buffer = [0; 64];
+/// let length = RunwayDatabase::encode_into(&mut runways, &mut buffer).unwrap();
+/// let database = RunwayDatabase::from_bytes(&buffer[..length]).unwrap();
+///
+/// let nearest = database.nearest(&runways[0].location).unwrap();
+/// assert_eq!(nearest.designator.as_str(), "23");
+/// ```
+#[derive(Clone, PartialEq)]
+pub struct RunwayDatabase<D = &'static [u8]> {
+    data: D,
+}
+
+impl<D: AsRef<[u8]>> RunwayDatabase<D> {
+    /// Use the binary form of a runway database
+    ///
+    /// Checks the header, the identifiers and the sorting of all records.
+    pub fn from_bytes(data: D) -> Result<Self, RunwayDatabaseError> {
+        let bytes = data.as_ref();
+
+        if bytes.len() < HEADER_LENGTH || &bytes[..4] != MAGIC {
+            return Err(RunwayDatabaseError::InvalidFormat("missing header"));
+        }
+
+        let count = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as usize;
+        let length = count
+            .checked_mul(RECORD_LENGTH)
+            .and_then(|records| records.checked_add(HEADER_LENGTH));
+        if length != Some(bytes.len()) {
+            return Err(RunwayDatabaseError::InvalidFormat("length does not match"));
+        }
+
+        let database = Self { data };
+        let mut last_latitude = i32::MIN;
+        for index in 0..count {
+            let record = database.record(index);
+
+            if !record[..16].is_ascii() {
+                return Err(RunwayDatabaseError::InvalidFormat(
+                    "identifier is not ASCII",
+                ));
+            }
+
+            let latitude = read_i32(record, 16);
+            if latitude < last_latitude {
+                return Err(RunwayDatabaseError::InvalidFormat("not sorted by latitude"));
+            }
+            last_latitude = latitude;
+        }
+
+        Ok(database)
+    }
+
+    /// The binary form of this database
+    pub fn as_bytes(&self) -> &[u8] {
+        self.data.as_ref()
+    }
+
+    /// Number of runway thresholds in this database
+    pub fn len(&self) -> usize {
+        (self.data.as_ref().len() - HEADER_LENGTH) / RECORD_LENGTH
+    }
+
+    /// Returns `true` if this database contains no runway thresholds
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterates over all runway thresholds, from south to north
+    pub fn iter(&self) -> impl Iterator<Item = Runway> + '_ {
+        (0..self.len()).map(move |index| self.runway(index))
+    }
+
+    /// The runway threshold closest to a position, `None` if the database is empty
+    pub fn nearest(&self, position: &Position) -> Option<Runway> {
+        let start = self.partition_point(position.latitude);
+        let (mut south, mut north) = (start, start);
+        let mut nearest: Option<(Length, Runway)> = None;
+
+        // Walk outwards from the latitude of the position. The latitude difference alone is a
+        // lower bound for the distance, so the search is done once it exceeds the best distance.
+        loop {
+            let south_bound = south
+                .checked_sub(1)
+                .map(|index| latitude_distance(position, &self.runway(index)));
+            let north_bound =
+                (north < self.len()).then(|| latitude_distance(position, &self.runway(north)));
+
+            let take_south = match (south_bound, north_bound) {
+                (Some(south), Some(north)) => south <= north,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
+            let bound = if take_south { south_bound } else { north_bound };
+
+            if let (Some(bound), Some((distance, _))) = (bound, &nearest) {
+                if bound > *distance {
+                    break;
+                }
+            }
+
+            let runway = if take_south {
+                south -= 1;
+                self.runway(south)
+            } else {
+                north += 1;
+                self.runway(north - 1)
+            };
+
+            let distance = position.distance(&runway.location);
+            let closer = !matches!(nearest, Some((nearest, _)) if nearest <= distance);
+            if closer {
+                nearest = Some((distance, runway));
+            }
+        }
+
+        nearest.map(|(_, runway)| runway)
+    }
+
+    /// Iterates over all runway thresholds within a radius around a position
+    pub fn within<'a>(
+        &'a self,
+        position: &'a Position,
+        radius: Length,
+    ) -> impl Iterator<Item = Runway> + 'a {
+        let band = Angle::new::<uom::si::angle::radian>((radius / earth_radius()).value);
+        let start = self.partition_point(position.latitude - band);
+        let end = self.partition_point(position.latitude + band);
+
+        (start..end)
+            .map(move |index| self.runway(index))
+            .filter(move |runway| position.distance(&runway.location) <= radius)
+    }
+
+    /// Index of the first record with a latitude of at least `latitude`
+    fn partition_point(&self, latitude: Angle) -> usize {
+        let latitude = encode_angle(latitude);
+        let (mut low, mut high) = (0, self.len());
+
+        while low < high {
+            let middle = low + (high - low) / 2;
+            match read_i32(self.record(middle), 16).cmp(&latitude) {
+                Ordering::Less => low = middle + 1,
+                _ => high = middle,
+            }
+        }
+
+        low
+    }
+
+    fn record(&self, index: usize) -> &[u8] {
+        let start = HEADER_LENGTH + index * RECORD_LENGTH;
+        &self.data.as_ref()[start..start + RECORD_LENGTH]
+    }
+
+    fn runway(&self, index: usize) -> Runway {
+        let record = self.record(index);
+
+        Runway {
+            airport: read_identifier(&record[0..8]),
+            designator: read_identifier(&record[8..16]),
+            location: Position {
+                latitude: Angle::new::<degree>(f64::from(read_i32(record, 16)) / ANGLE_SCALE),
+                longitude: Angle::new::<degree>(f64::from(read_i32(record, 20)) / ANGLE_SCALE),
+                altitude_sea: Length::new::<foot>(f64::from(i16::from_le_bytes([
+                    record[24], record[25],
+                ]))),
+            },
+            azimuth: Angle::new::<degree>(
+                f64::from(u16::from_le_bytes([record[26], record[27]])) / HEADING_SCALE,
+            ),
+            length: Length::new::<foot>(f64::from(u16::from_le_bytes([record[28], record[29]]))),
+        }
+    }
+}
+
+impl RunwayDatabase {
+    /// Write the binary form of a runway database into a buffer, returning the number of bytes
+    /// written
+    ///
+    /// The runways are sorted by latitude in place. Elevations, headings and lengths are rounded
+    /// to the resolution of the binary form.
+    pub fn encode_into(
+        runways: &mut [Runway],
+        buffer: &mut [u8],
+    ) -> Result<usize, RunwayDatabaseError> {
+        let length = HEADER_LENGTH + runways.len() * RECORD_LENGTH;
+        if buffer.len() < length {
+            return Err(RunwayDatabaseError::BufferTooSmall);
+        }
+
+        runways.sort_unstable_by_key(|runway| encode_angle(runway.location.latitude));
+
+        buffer[..4].copy_from_slice(MAGIC);
+        buffer[4..8].copy_from_slice(&(runways.len() as u32).to_le_bytes());
+
+        for (runway, record) in runways
+            .iter()
+            .zip(buffer[HEADER_LENGTH..length].chunks_exact_mut(RECORD_LENGTH))
+        {
+            let elevation = runway.location.altitude_sea.get::<foot>();
+            let heading = libm::fmod(runway.azimuth.get::<degree>() + 360.0, 360.0);
+
+            record[0..8].copy_from_slice(&runway.airport.bytes);
+            record[8..16].copy_from_slice(&runway.designator.bytes);
+            record[16..20].copy_from_slice(&encode_angle(runway.location.latitude).to_le_bytes());
+            record[20..24].copy_from_slice(&encode_angle(runway.location.longitude).to_le_bytes());
+            record[24..26].copy_from_slice(&(round(elevation) as i16).to_le_bytes());
+            record[26..28].copy_from_slice(&(round(heading * HEADING_SCALE) as u16).to_le_bytes());
+            record[28..30]
+                .copy_from_slice(&(round(runway.length.get::<foot>()) as u16).to_le_bytes());
+        }
+
+        Ok(length)
+    }
+}
+
+#[cfg(feature = "std")]
+impl RunwayDatabase<std::vec::Vec<u8>> {
+    /// Build a runway database from runways
+    pub fn new(runways: &mut [Runway]) -> Self {
+        let mut data = std::vec![0; HEADER_LENGTH + runways.len() * RECORD_LENGTH];
+        let length = RunwayDatabase::encode_into(runways, &mut data)
+            .expect("the buffer is sized for all runways");
+        data.truncate(length);
+
+        Self { data }
+    }
+
+    /// Build a runway database from the `airports.csv` and `runways.csv` files of OurAirports
+    ///
+    /// Every runway end with a known position becomes a runway threshold, shifted by its
+    /// displaced threshold. Closed runways are skipped. Missing threshold elevations are taken
+    /// from the airport, missing headings are derived from the position of the opposite runway
+    /// end. Unknown lengths are stored as zero.
+    pub fn from_ourairports<A: std::io::Read, R: std::io::Read>(
+        airports: A,
+        runways: R,
+    ) -> Result<Self, RunwayDatabaseError> {
+        let mut runways = ourairports::runways(airports, runways)?;
+        Ok(Self::new(&mut runways))
+    }
+}
+
+impl<D: AsRef<[u8]>> fmt::Debug for RunwayDatabase<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RunwayDatabase")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl<D: AsRef<[u8]> + Sync> RunwayServer for RunwayDatabase<D> {
+    fn nearest_runway(&self, position: &Position) -> Result<Option<Runway>, TerrainError> {
+        Ok(self.nearest(position))
+    }
+}
+
+fn earth_radius() -> Length {
+    Length::new::<kilometer>(6371.0)
+}
+
+/// Distance between the latitudes of a position and a runway, a lower bound of their distance
+fn latitude_distance(position: &Position, runway: &Runway) -> Length {
+    let difference = (position.latitude - runway.location.latitude)
+        .get::<uom::si::angle::radian>()
+        .abs();
+    earth_radius() * difference
+}
+
+fn encode_angle(angle: Angle) -> i32 {
+    round(angle.get::<degree>() * ANGLE_SCALE) as i32
+}
+
+fn round(value: f64) -> f64 {
+    libm::round(value)
+}
+
+fn read_i32(record: &[u8], offset: usize) -> i32 {
+    i32::from_le_bytes([
+        record[offset],
+        record[offset + 1],
+        record[offset + 2],
+        record[offset + 3],
+    ])
+}
+
+fn read_identifier(field: &[u8]) -> Identifier {
+    let mut bytes = [0; Identifier::CAPACITY];
+    bytes.copy_from_slice(field);
+    let len = field
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(field.len());
+
+    Identifier {
+        bytes,
+        len: len as u8,
+    }
+}
+
+/// Import of the CSV files published by OurAirports
+#[cfg(feature = "std")]
+mod ourairports {
+    use std::{
+        collections::HashMap,
+        io::{BufRead, BufReader, Read},
+        string::String,
+        vec::Vec,
+    };
+
+    use super::*;
+
+    /// Columns of `runways.csv` which describe one runway end
+    struct End {
+        ident: &'static str,
+        latitude: &'static str,
+        longitude: &'static str,
+        elevation: &'static str,
+        heading: &'static str,
+        displaced_threshold: &'static str,
+    }
+
+    const LOW_END: End = End {
+        ident: "le_ident",
+        latitude: "le_latitude_deg",
+        longitude: "le_longitude_deg",
+        elevation: "le_elevation_ft",
+        heading: "le_heading_degT",
+        displaced_threshold: "le_displaced_threshold_ft",
+    };
+
+    const HIGH_END: End = End {
+        ident: "he_ident",
+        latitude: "he_latitude_deg",
+        longitude: "he_longitude_deg",
+        elevation: "he_elevation_ft",
+        heading: "he_heading_degT",
+        displaced_threshold: "he_displaced_threshold_ft",
+    };
+
+    /// A CSV file with a header line
+    struct Csv<R> {
+        header: Vec<String>,
+        lines: std::iter::Enumerate<std::io::Lines<BufReader<R>>>,
+    }
+
+    /// A row of a CSV file
+    struct Row<'a> {
+        header: &'a [String],
+        fields: Vec<String>,
+    }
+
+    impl<R: Read> Csv<R> {
+        fn new(reader: R) -> Result<Self, RunwayDatabaseError> {
+            let mut lines = BufReader::new(reader).lines().enumerate();
+            let header = match lines.next() {
+                Some((_, line)) => fields(&line?),
+                None => Vec::new(),
+            };
+
+            Ok(Self { header, lines })
+        }
+
+        fn require(&self, columns: &[&'static str]) -> Result<(), RunwayDatabaseError> {
+            match columns
+                .iter()
+                .find(|column| !self.header.iter().any(|name| name == *column))
+            {
+                Some(column) => Err(RunwayDatabaseError::MissingColumn(column)),
+                None => Ok(()),
+            }
+        }
+
+        /// Calls `f` for every row of the file
+        fn for_each<F>(&mut self, mut f: F) -> Result<(), RunwayDatabaseError>
+        where
+            F: FnMut(&Row) -> Result<(), RunwayDatabaseError>,
+        {
+            for (index, line) in &mut self.lines {
+                let line = line?;
+                if line.is_empty() {
+                    continue;
+                }
+
+                let fields = fields(&line);
+                if fields.len() != self.header.len() {
+                    return Err(RunwayDatabaseError::InvalidRow { line: index + 1 });
+                }
+
+                f(&Row {
+                    header: &self.header,
+                    fields,
+                })?;
+            }
+
+            Ok(())
+        }
+    }
+
+    impl Row<'_> {
+        /// The content of a column, `None` if it is empty
+        fn get(&self, column: &str) -> Option<&str> {
+            let index = self.header.iter().position(|name| name == column)?;
+            Some(self.fields[index].as_str()).filter(|field| !field.is_empty())
+        }
+
+        fn number(&self, column: &str) -> Option<f64> {
+            self.get(column)?.parse().ok()
+        }
+    }
+
+    /// Splits a CSV line into its fields, removing quotes
+    fn fields(line: &str) -> Vec<String> {
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut chars = line.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if quoted && chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => quoted = !quoted,
+                ',' if !quoted => fields.push(std::mem::take(&mut field)),
+                _ => field.push(c),
+            }
+        }
+        fields.push(field);
+
+        fields
+    }
+
+    pub(super) fn runways<A: Read, R: Read>(
+        airports: A,
+        runways: R,
+    ) -> Result<Vec<Runway>, RunwayDatabaseError> {
+        let mut airport_elevations = HashMap::new();
+        let mut airports = Csv::new(airports)?;
+        airports.require(&["ident", "elevation_ft"])?;
+        airports.for_each(|row| {
+            if let (Some(ident), Some(elevation)) = (row.get("ident"), row.number("elevation_ft")) {
+                airport_elevations.insert(String::from(ident), elevation);
+            }
+            Ok(())
+        })?;
+
+        let mut result = Vec::new();
+        let mut runways = Csv::new(runways)?;
+        runways.require(&["airport_ident", "length_ft", "closed"])?;
+        for end in &[LOW_END, HIGH_END] {
+            runways.require(&[
+                end.ident,
+                end.latitude,
+                end.longitude,
+                end.elevation,
+                end.heading,
+                end.displaced_threshold,
+            ])?;
+        }
+
+        runways.for_each(|row| {
+            if row.get("closed") == Some("1") {
+                return Ok(());
+            }
+
+            let airport_ident = row.get("airport_ident").unwrap_or_default();
+            let airport = match Identifier::new(airport_ident) {
+                Some(airport) => airport,
+                None => return Ok(()),
+            };
+            let airport_elevation = airport_elevations.get(airport_ident).copied();
+            let length = Length::new::<foot>(row.number("length_ft").unwrap_or(0.0));
+
+            let low_end = position(row, &LOW_END);
+            let high_end = position(row, &HIGH_END);
+
+            for (end, location, opposite) in [
+                (&LOW_END, low_end, high_end),
+                (&HIGH_END, high_end, low_end),
+            ] {
+                let designator = row.get(end.ident).and_then(Identifier::new);
+                let elevation = row.number(end.elevation).or(airport_elevation);
+
+                let (designator, mut location, elevation) = match (designator, location, elevation)
+                {
+                    (Some(designator), Some(location), Some(elevation)) => {
+                        (designator, location, elevation)
+                    }
+                    _ => continue,
+                };
+                location.altitude_sea = Length::new::<foot>(elevation);
+
+                let azimuth = match (row.number(end.heading), opposite) {
+                    (Some(heading), _) => Angle::new::<degree>(heading),
+                    (None, Some(opposite)) => location.bearing(&opposite),
+                    (None, None) => continue,
+                };
+
+                if let Some(displaced) = row.number(end.displaced_threshold) {
+                    location = location.destination(azimuth, Length::new::<foot>(displaced));
+                }
+
+                result.push(Runway {
+                    airport,
+                    designator,
+                    location,
+                    length,
+                    azimuth,
+                });
+            }
+
+            Ok(())
+        })?;
+
+        Ok(result)
+    }
+
+    /// Position of a runway end, `None` if it is unknown
+    fn position(row: &Row, end: &End) -> Option<Position> {
+        Some(Position {
+            latitude: Angle::new::<degree>(row.number(end.latitude)?),
+            longitude: Angle::new::<degree>(row.number(end.longitude)?),
+            altitude_sea: Length::new::<foot>(0.0),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use uom::si::length::nautical_mile;
+
+    fn runway(airport: &str, designator: &str, latitude: f64, longitude: f64) -> Runway {
+        Runway {
+            airport: Identifier::new(airport).unwrap(),
+            designator: Identifier::new(designator).unwrap(),
+            location: Position {
+                latitude: Angle::new::<degree>(latitude),
+                longitude: Angle::new::<degree>(longitude),
+                altitude_sea: Length::new::<foot>(53.0),
+            },
+            length: Length::new::<foot>(10663.0),
+            azimuth: Angle::new::<degree>(233.5),
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-6,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    fn position(latitude: f64, longitude: f64) -> Position {
+        Position {
+            latitude: Angle::new::<degree>(latitude),
+            longitude: Angle::new::<degree>(longitude),
+            altitude_sea: Length::new::<foot>(0.0),
+        }
+    }
+
+    /// Runways spread over northern Germany, encoded into `buffer`
+    fn database(buffer: &mut [u8]) -> RunwayDatabase<&[u8]> {
+        let mut runways = [
+            runway("EDDH", "23", 53.6473, 10.0126),
+            runway("EDDH", "33", 53.6183, 9.9781),
+            runway("EDHI", "23", 53.5427, 9.8436),
+            runway("EDDW", "27", 53.0476, 8.8052),
+            runway("EDDV", "27R", 52.4586, 9.7003),
+            runway("EDXW", "32", 54.9069, 8.3458),
+        ];
+        let length = RunwayDatabase::encode_into(&mut runways, buffer).unwrap();
+        RunwayDatabase::from_bytes(&buffer[..length]).unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut buffer = [0; 256];
+        let database = database(&mut buffer);
+        assert_eq!(database.len(), 6);
+
+        let runway = database
+            .iter()
+            .find(|runway| runway.airport.as_str() == "EDHI")
+            .unwrap();
+        assert_eq!(runway.designator.as_str(), "23");
+        assert!((runway.location.latitude.get::<degree>() - 53.5427).abs() < 1e-7);
+        assert!((runway.location.longitude.get::<degree>() - 9.8436).abs() < 1e-7);
+        assert_close(runway.location.altitude_sea.get::<foot>(), 53.0);
+        assert_close(runway.azimuth.get::<degree>(), 233.5);
+        assert_close(runway.length.get::<foot>(), 10663.0);
+    }
+
+    #[test]
+    fn sorted_by_latitude() {
+        let mut buffer = [0; 256];
+        let database = database(&mut buffer);
+
+        let mut last_latitude = Angle::new::<degree>(-90.0);
+        for runway in database.iter() {
+            assert!(runway.location.latitude >= last_latitude);
+            last_latitude = runway.location.latitude;
+        }
+    }
+
+    #[test]
+    fn nearest() {
+        let mut buffer = [0; 256];
+        let database = database(&mut buffer);
+
+        let nearest = |latitude, longitude| database.nearest(&position(latitude, longitude));
+        assert_eq!(nearest(53.64, 10.0).unwrap().designator.as_str(), "23");
+        assert_eq!(nearest(53.62, 9.97).unwrap().designator.as_str(), "33");
+        assert_eq!(nearest(53.0, 8.0).unwrap().airport.as_str(), "EDDW");
+        assert_eq!(nearest(60.0, 10.0).unwrap().airport.as_str(), "EDXW");
+        assert_eq!(nearest(0.0, 9.7).unwrap().airport.as_str(), "EDDV");
+    }
+
+    #[test]
+    fn nearest_matches_exhaustive_search() {
+        let mut buffer = [0; 256];
+        let database = database(&mut buffer);
+
+        for latitude in (0..40).map(|step| 52.0 + f64::from(step) * 0.08) {
+            for longitude in (0..40).map(|step| 8.0 + f64::from(step) * 0.06) {
+                let position = position(latitude, longitude);
+                let expected = database
+                    .iter()
+                    .map(|runway| position.distance(&runway.location))
+                    .fold(f64::INFINITY, |min, distance| {
+                        min.min(distance.get::<foot>())
+                    });
+                let nearest = database.nearest(&position).unwrap();
+
+                assert_eq!(position.distance(&nearest.location).get::<foot>(), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn within_radius() {
+        let mut buffer = [0; 256];
+        let database = database(&mut buffer);
+        let hamburg = position(53.63, 10.0);
+
+        let count = |radius| {
+            database
+                .within(&hamburg, Length::new::<nautical_mile>(radius))
+                .count()
+        };
+        assert_eq!(count(0.5), 0);
+        assert_eq!(count(5.0), 2);
+        assert_eq!(count(10.0), 3);
+        assert_eq!(count(100.0), 6);
+    }
+
+    #[test]
+    fn empty_database() {
+        let mut buffer = [0; 8];
+        let length = RunwayDatabase::encode_into(&mut [], &mut buffer).unwrap();
+        let database = RunwayDatabase::from_bytes(&buffer[..length]).unwrap();
+
+        assert!(database.is_empty());
+        assert_eq!(database.nearest(&position(0.0, 0.0)), None);
+    }
+
+    #[test]
+    fn invalid_binary_form() {
+        let mut buffer = [0; 256];
+        let length = database(&mut buffer).as_bytes().len();
+
+        assert!(RunwayDatabase::from_bytes(&buffer[..length - 1]).is_err());
+
+        // a count whose length overflows on narrow targets
+        let mut huge = [0; HEADER_LENGTH];
+        huge[..4].copy_from_slice(MAGIC);
+        huge[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            RunwayDatabase::from_bytes(&huge[..]),
+            Err(RunwayDatabaseError::InvalidFormat(_))
+        ));
+        assert!(
+            RunwayDatabase::encode_into(&mut [runway("EDDH", "23", 0.0, 0.0)], &mut [0; 37])
+                .is_err()
+        );
+
+        // swap the latitudes of the first two records
+        let (south, north) = (HEADER_LENGTH + 16, HEADER_LENGTH + RECORD_LENGTH + 16);
+        for offset in 0..4 {
+            buffer.swap(south + offset, north + offset);
+        }
+        assert!(matches!(
+            RunwayDatabase::from_bytes(&buffer[..length]),
+            Err(RunwayDatabaseError::InvalidFormat(_))
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn import_ourairports() {
+        let airports = r#""id","ident","type","name","latitude_deg","longitude_deg","elevation_ft"
+2212,"EDDH","large_airport","Hamburg Helmut Schmidt Airport, Fuhlsbüttel",53.630402,9.988230,53
+2217,"EDHI","small_airport","Hamburg-Finkenwerder Airport",53.5355,9.8355,23
+"#;
+        let runways = r#""id","airport_ref","airport_ident","length_ft","width_ft","surface","lighted","closed","le_ident","le_latitude_deg","le_longitude_deg","le_elevation_ft","le_heading_degT","le_displaced_threshold_ft","he_ident","he_latitude_deg","he_longitude_deg","he_elevation_ft","he_heading_degT","he_displaced_threshold_ft"
+1,2212,"EDDH",10663,151,"ASP",1,0,"05",53.6195,9.9728,38,53.5,,"23",53.6473,10.0126,44,233.5,820
+2,2212,"EDDH",12028,151,"ASP",1,0,"15",53.6488,9.9686,,,,"33",53.6183,9.9781,,,
+3,2217,"EDHI",10390,148,"ASP",1,1,"05",53.5325,9.8177,22,,,"23",53.5427,9.8436,22,,
+4,2217,"EDHI",1000,20,"GRS",0,0,"H1",,,,,,,,,,,
+"#;
+
+        let database =
+            RunwayDatabase::from_ourairports(airports.as_bytes(), runways.as_bytes()).unwrap();
+        assert_eq!(database.len(), 4);
+
+        let find = |designator: &str| {
+            database
+                .iter()
+                .find(|runway| runway.designator.as_str() == designator)
+                .unwrap()
+        };
+
+        let runway_05 = find("05");
+        assert_eq!(runway_05.airport.as_str(), "EDDH");
+        assert_close(runway_05.location.altitude_sea.get::<foot>(), 38.0);
+        assert_close(runway_05.azimuth.get::<degree>(), 53.5);
+        assert_close(runway_05.length.get::<foot>(), 10663.0);
+
+        // displaced thresholds are moved into the runway direction
+        let runway_23 = find("23");
+        let end_23 = position(53.6473, 10.0126);
+        let displacement = runway_23.location.distance(&end_23).get::<foot>();
+        assert!((displacement - 820.0).abs() < 1.0);
+
+        // missing elevations and headings are taken from the airport and the opposite end
+        let runway_15 = find("15");
+        assert_close(runway_15.location.altitude_sea.get::<foot>(), 53.0);
+        let heading = runway_15.azimuth.get::<degree>();
+        assert!((heading - 169.0).abs() < 1.0, "heading {}", heading);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn import_missing_column() {
+        let result = RunwayDatabase::from_ourairports(
+            "\"ident\",\"elevation_ft\"\n".as_bytes(),
+            "\"airport_ident\"\n".as_bytes(),
+        );
+
+        assert!(matches!(
+            result,
+            Err(RunwayDatabaseError::MissingColumn("length_ft"))
+        ));
+    }
+}
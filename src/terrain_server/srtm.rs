@@ -0,0 +1,446 @@
+//! Terrain source for Shuttle Radar Topography Mission (SRTM) `.hgt` tiles
+//!
+//! Every tile covers one degree of latitude and longitude and is named after its south west
+//! corner, e.g. `N53E010.hgt`. It contains a square grid of big endian 16 bit elevations in meters,
+//! row by row from north to south. SRTM1 tiles have 3601 x 3601 posts, SRTM3 tiles 1201 x 1201.
+//! The outer rows and columns of neighbouring tiles overlap.
+
+use std::{
+    format, fs, io, iter,
+    path::PathBuf,
+    string::String,
+    sync::{Arc, Mutex, MutexGuard},
+    vec::Vec,
+};
+
+use uom::si::length::meter;
+
+use crate::prelude::*;
+
+/// Elevation which marks a void in the data
+const VOID: i16 = i16::MIN;
+
+/// Integer latitude and longitude of the south west corner of a tile
+type TileKey = (i16, i16);
+
+/// A single `.hgt` tile
+#[derive(Clone, Debug, PartialEq)]
+struct HgtTile {
+    /// Number of posts per row and column, minus one
+    samples: usize,
+
+    /// Elevations in meters, row by row from north to south
+    elevations: Vec<i16>,
+}
+
+impl HgtTile {
+    /// Parses the content of an `.hgt` file, `None` if it is not a square grid
+    ///
+    /// The resolution is derived from the size of the file. Besides SRTM1 and SRTM3 this also
+    /// accepts smaller grids.
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let posts = bytes.len() / 2;
+        let size = (posts as f64).sqrt() as usize;
+        if posts * 2 != bytes.len() || size < 2 || size * size != posts {
+            return None;
+        }
+
+        let elevations = bytes
+            .chunks_exact(2)
+            .map(|raw| i16::from_be_bytes([raw[0], raw[1]]))
+            .collect();
+
+        Some(Self {
+            samples: size - 1,
+            elevations,
+        })
+    }
+
+    /// Elevation of a post, counted from the south west corner
+    fn post(&self, row: usize, column: usize) -> Result<f64, TerrainError> {
+        match self.elevations[(self.samples - row) * (self.samples + 1) + column] {
+            VOID => Err(TerrainError::NoData),
+            elevation => Ok(f64::from(elevation)),
+        }
+    }
+}
+
+/// A cached tile, `None` if the tile does not exist
+type CachedTile = Result<Option<Arc<HgtTile>>, TerrainError>;
+
+/// Least recently used tiles, including tiles which are known to be missing or unreadable
+#[derive(Debug)]
+struct TileCache {
+    capacity: usize,
+
+    /// Cached tiles, the most recently used tile is at the end
+    tiles: Vec<(TileKey, CachedTile)>,
+}
+
+impl TileCache {
+    /// Get a tile and move it to the end of the cache, `None` if it is not cached
+    fn get(&mut self, key: TileKey) -> CachedTile {
+        match self.tiles.iter().position(|(cached, _)| *cached == key) {
+            Some(index) => {
+                let entry = self.tiles.remove(index);
+                let tile = entry.1.clone();
+                self.tiles.push(entry);
+                tile
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Move a tile to the end of the cache, `false` if it is not cached or could not be read
+    fn touch(&mut self, key: TileKey) -> bool {
+        let index = self
+            .tiles
+            .iter()
+            .position(|(cached, tile)| *cached == key && tile.is_ok());
+
+        if let Some(index) = index {
+            let entry = self.tiles.remove(index);
+            self.tiles.push(entry);
+        }
+        index.is_some()
+    }
+
+    /// Add a tile to the end of the cache, dropping the least recently used tile if full
+    fn insert(&mut self, key: TileKey, tile: CachedTile) {
+        self.tiles.retain(|(cached, _)| *cached != key);
+        let overflow = (self.tiles.len() + 1).saturating_sub(self.capacity);
+        self.tiles.drain(..overflow);
+        self.tiles.push((key, tile));
+    }
+}
+
+/// Terrain source which reads `.hgt` tiles from a directory
+///
+/// Reading a tile blocks for a while, so `elevation` never reads tiles itself. The tiles around
+/// the aircraft have to be read in advance by `preload`, outside of `Taws::process`. At most
+/// `capacity` tiles are kept in memory, the least recently used tile is dropped first.
+///
+/// Tiles which are not preloaded, missing tiles and voids result in `TerrainError::NoData`, while
+/// tiles which can not be read result in `TerrainError::Unavailable`.
+///
+/// # Example
+///
+/// ```no_run
+/// use opentaws::prelude::*;
+/// use opentaws::terrain_server::srtm::SrtmTerrain;
+///
+/// let terrain = SrtmTerrain::new("/usr/share/srtm", 9);
+///
+/// // repeat this whenever the aircraft moved, e.g. on a separate thread
+/// let position = Position {
+///     latitude: Angle::new::<degree>(53.6),
+///     longitude: Angle::new::<degree>(10.0),
+///     altitude_sea: Length::new::<foot>(0.0),
+/// };
+/// terrain.preload(&position).unwrap();
+///
+/// let config = TawsConfig {
+///     terrain_server: Some(&terrain),
+///     ..Default::default()
+/// };
+/// let taws = Taws::new(config).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct SrtmTerrain {
+    directory: PathBuf,
+    cache: Mutex<TileCache>,
+}
+
+impl SrtmTerrain {
+    /// Create a terrain source for the tiles in `directory`, caching at most `capacity` tiles
+    ///
+    /// A `capacity` of at least nine keeps all tiles of the last `preload` in memory.
+    pub fn new<P: Into<PathBuf>>(directory: P, capacity: usize) -> Self {
+        Self {
+            directory: directory.into(),
+            cache: Mutex::new(TileCache {
+                capacity: capacity.max(1),
+                tiles: Vec::new(),
+            }),
+        }
+    }
+
+    /// Name of the tile which covers a position, e.g. `N53E010`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use opentaws::prelude::*;
+    /// use opentaws::terrain_server::srtm::SrtmTerrain;
+    ///
+    /// let position = Position {
+    ///     latitude: Angle::new::<degree>(-0.5),
+    ///     longitude: Angle::new::<degree>(10.5),
+    ///     altitude_sea: Length::new::<foot>(0.0),
+    /// };
+    /// assert_eq!(SrtmTerrain::tile_name(&position), "S01E010");
+    /// ```
+    pub fn tile_name(position: &Position) -> String {
+        Self::name(Self::key(position))
+    }
+
+    fn key(position: &Position) -> TileKey {
+        (
+            position.latitude.get::<degree>().floor() as i16,
+            position.longitude.get::<degree>().floor() as i16,
+        )
+    }
+
+    fn name((latitude, longitude): TileKey) -> String {
+        format!(
+            "{}{:02}{}{:03}",
+            if latitude < 0 { 'S' } else { 'N' },
+            latitude.unsigned_abs(),
+            if longitude < 0 { 'W' } else { 'E' },
+            longitude.unsigned_abs()
+        )
+    }
+
+    /// Read the tile which covers `position` and its eight neighbours into the cache
+    ///
+    /// Tiles which are cached already are not read again. This blocks while reading the tiles, but
+    /// not the concurrent calls of `elevation`. Fails with `TerrainError::Unavailable` if one of
+    /// the tiles can not be read, the other tiles are preloaded nevertheless.
+    pub fn preload(&self, position: &Position) -> Result<(), TerrainError> {
+        let (latitude, longitude) = Self::key(position);
+        let neighbours = (-1..=1)
+            .flat_map(|south_north| (-1..=1).map(move |west_east| (south_north, west_east)))
+            .filter(|offset| *offset != (0, 0))
+            .map(|(south_north, west_east)| (latitude + south_north, longitude + west_east));
+
+        // the tile below the aircraft comes last, so that it is dropped last
+        neighbours
+            .chain(iter::once((latitude, longitude)))
+            .map(|key| self.load(key))
+            .fold(Ok(()), Result::and)
+    }
+
+    /// Read a tile into the cache, unless it is cached already
+    fn load(&self, key: TileKey) -> Result<(), TerrainError> {
+        if self.cache()?.touch(key) {
+            return Ok(());
+        }
+
+        // the cache is not locked while reading, which would block `elevation`
+        let path = self.directory.join(Self::name(key)).with_extension("hgt");
+        let tile = match fs::read(path) {
+            Ok(bytes) => HgtTile::from_bytes(&bytes)
+                .map(|tile| Some(Arc::new(tile)))
+                .ok_or(TerrainError::Unavailable),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(_) => Err(TerrainError::Unavailable),
+        };
+
+        self.cache()?.insert(key, tile.clone());
+        tile.map(|_| ())
+    }
+
+    /// Get a tile from the cache, `None` if the tile does not exist or is not preloaded
+    fn tile(&self, key: TileKey) -> CachedTile {
+        self.cache()?.get(key)
+    }
+
+    fn cache(&self) -> Result<MutexGuard<'_, TileCache>, TerrainError> {
+        self.cache.lock().map_err(|_| TerrainError::Unavailable)
+    }
+
+    /// Elevation of a post given in posts from latitude and longitude zero
+    ///
+    /// Posts on the edge of a tile are shared with the neighbouring tile. If the tile north or east
+    /// of the edge is missing, the post is taken from the tile south or west of it.
+    fn post(&self, samples: usize, row: i64, column: i64) -> Result<f64, TerrainError> {
+        for (latitude, row) in Self::owners(samples, row) {
+            for (longitude, column) in Self::owners(samples, column) {
+                match self.tile((latitude, longitude))? {
+                    Some(tile) if tile.samples == samples => return tile.post(row, column),
+                    _ => continue,
+                }
+            }
+        }
+
+        Err(TerrainError::NoData)
+    }
+
+    /// Tiles which contain a post along one axis together with the offset of the post within
+    /// them, preferring the tile in which the post is the first one
+    fn owners(samples: usize, post: i64) -> impl Iterator<Item = (i16, usize)> {
+        let tile = post.div_euclid(samples as i64);
+        let offset = post.rem_euclid(samples as i64) as usize;
+        let shared = (offset == 0).then_some(((tile - 1) as i16, samples));
+
+        iter::once((tile as i16, offset)).chain(shared)
+    }
+}
+
+/// `elevation` only looks up the tiles cached by `preload`, it never reads a tile on a miss. A
+/// lookup counts as a use of the tile, so the tiles below the aircraft stay cached.
+impl TerrainServer for SrtmTerrain {
+    /// Bilinear interpolation of the elevation between the four surrounding posts
+    fn elevation(&self, position: &Position) -> Result<Length, TerrainError> {
+        let samples = self
+            .tile(Self::key(position))?
+            .ok_or(TerrainError::NoData)?
+            .samples;
+
+        let y = position.latitude.get::<degree>() * samples as f64;
+        let x = position.longitude.get::<degree>() * samples as f64;
+        let (row, column) = (y.floor() as i64, x.floor() as i64);
+        let (dy, dx) = (y - row as f64, x - column as f64);
+
+        let south = self.post(samples, row, column)? * (1.0 - dx)
+            + self.post(samples, row, column + 1)? * dx;
+        let north = self.post(samples, row + 1, column)? * (1.0 - dx)
+            + self.post(samples, row + 1, column + 1)? * dx;
+
+        Ok(Length::new::<meter>(south * (1.0 - dy) + north * dy))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::path::Path;
+
+    /// The fixtures are 3 x 3 post tiles, each post adds 100 m to the east and 10 m to the north.
+    /// `N53E010` starts at 0 m, `N53E011` continues at 200 m and has a void in its north east
+    /// corner. `N54E010` is missing.
+    fn terrain(capacity: usize) -> SrtmTerrain {
+        SrtmTerrain::new(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/srtm"),
+            capacity,
+        )
+    }
+
+    fn position(latitude: f64, longitude: f64) -> Position {
+        Position {
+            latitude: Angle::new::<degree>(latitude),
+            longitude: Angle::new::<degree>(longitude),
+            altitude_sea: Length::new::<meter>(0.0),
+        }
+    }
+
+    fn assert_elevation(actual: Result<Length, TerrainError>, expected: f64) {
+        let actual = actual.unwrap().get::<meter>();
+        assert!(
+            (actual - expected).abs() < 1e-6,
+            "expected {} m, got {} m",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn tile_names() {
+        assert_eq!(SrtmTerrain::tile_name(&position(53.5, 10.5)), "N53E010");
+        assert_eq!(SrtmTerrain::tile_name(&position(-0.5, -0.5)), "S01W001");
+        assert_eq!(SrtmTerrain::tile_name(&position(-33.9, 151.2)), "S34E151");
+        assert_eq!(SrtmTerrain::tile_name(&position(0.0, -180.0)), "N00W180");
+    }
+
+    #[test]
+    fn bilinear_interpolation() {
+        let terrain = terrain(9);
+        terrain.preload(&position(53.5, 10.5)).unwrap();
+
+        assert_elevation(terrain.elevation(&position(53.0, 10.0)), 0.0);
+        assert_elevation(terrain.elevation(&position(53.25, 10.25)), 55.0);
+        assert_elevation(terrain.elevation(&position(53.5, 10.75)), 160.0);
+    }
+
+    #[test]
+    fn interpolation_across_tiles() {
+        let terrain = terrain(9);
+        terrain.preload(&position(53.5, 10.5)).unwrap();
+
+        // the eastern posts are taken from N53E011
+        assert_elevation(terrain.elevation(&position(53.5, 10.9)), 190.0);
+
+        // the northern posts would belong to the missing N54E010, but are shared with N53E010
+        assert_elevation(terrain.elevation(&position(53.9, 10.5)), 118.0);
+    }
+
+    #[test]
+    fn voids_and_missing_tiles_are_no_data() {
+        let terrain = terrain(9);
+        terrain.preload(&position(53.5, 11.5)).unwrap();
+        terrain.preload(&position(10.0, 10.0)).unwrap();
+
+        assert_eq!(
+            terrain.elevation(&position(53.9, 11.9)),
+            Err(TerrainError::NoData)
+        );
+        assert_eq!(
+            terrain.elevation(&position(10.0, 10.0)),
+            Err(TerrainError::NoData)
+        );
+    }
+
+    #[test]
+    fn tiles_are_only_read_by_preload() {
+        let terrain = terrain(9);
+
+        assert_eq!(
+            terrain.elevation(&position(53.25, 10.25)),
+            Err(TerrainError::NoData)
+        );
+        assert!(terrain.cache.lock().unwrap().tiles.is_empty());
+
+        terrain.preload(&position(53.5, 10.5)).unwrap();
+        assert_elevation(terrain.elevation(&position(53.25, 10.25)), 55.0);
+    }
+
+    #[test]
+    fn cache_is_bounded() {
+        let terrain = terrain(1);
+        terrain.preload(&position(53.5, 10.5)).unwrap();
+
+        assert_elevation(terrain.elevation(&position(53.25, 10.25)), 55.0);
+        assert_eq!(
+            terrain.elevation(&position(53.25, 11.25)),
+            Err(TerrainError::NoData)
+        );
+        assert_eq!(terrain.cache.lock().unwrap().tiles.len(), 1);
+    }
+
+    #[test]
+    fn tiles_of_the_last_preload_are_kept() {
+        let terrain = terrain(9);
+
+        terrain.preload(&position(53.5, 10.5)).unwrap();
+        terrain.preload(&position(53.5, 11.5)).unwrap();
+
+        let cache = terrain.cache.lock().unwrap();
+        let keys: Vec<_> = cache.tiles.iter().map(|(key, _)| *key).collect();
+        assert_eq!(keys.len(), 9);
+        assert!(keys.iter().all(|(_, longitude)| *longitude >= 10));
+        assert_eq!(keys.last(), Some(&(53, 11)));
+    }
+
+    #[test]
+    fn least_recently_used_tiles_are_dropped() {
+        let mut cache = TileCache {
+            capacity: 2,
+            tiles: Vec::new(),
+        };
+
+        cache.insert((53, 10), Ok(None));
+        cache.insert((53, 11), Ok(None));
+        cache.get((53, 10)).unwrap();
+        cache.insert((53, 12), Ok(None));
+
+        let keys: Vec<_> = cache.tiles.iter().map(|(key, _)| *key).collect();
+        assert_eq!(keys, [(53, 10), (53, 12)]);
+    }
+
+    #[test]
+    fn invalid_tiles_are_rejected() {
+        assert_eq!(HgtTile::from_bytes(&[0; 10]), None);
+        assert_eq!(HgtTile::from_bytes(&[0; 2]), None);
+        assert!(HgtTile::from_bytes(&[0; 8]).is_some());
+    }
+}
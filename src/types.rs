@@ -15,6 +15,10 @@ use uom::{
     },
 };
 
+use crate::alerts::{Alert, AlertLevel, AlertSubType, Callout};
+use crate::priorities::AlertPriorities;
+use crate::terrain_server::{RunwayServer, TerrainServer};
+
 /// Represents the current state of an aircraft
 #[derive(Clone, Debug, Default)]
 #[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
@@ -62,16 +66,253 @@ pub struct AircraftState {
 
     /// Whether steep approach is selected
     pub steep_approach: bool,
+
+    /// Type of the selected approach
+    pub approach: ApproachType,
+
+    /// Position of the flaps
+    pub flaps: FlapPosition,
+
+    /// Position of the landing gear
+    pub landing_gear: GearPosition,
+
+    /// Whether the weight of the aircraft rests on its wheels
+    pub weight_on_wheels: Discrete,
+
+    /// Whether the crew inhibits the terrain alerts with the terrain inhibit switch
+    ///
+    /// This only affects the functions relying on the terrain database, FLTA and PDA. Modes 1 to 5
+    /// keep alerting, they are inhibited through `Taws::inhibit` instead.
+    pub terrain_inhibit: Discrete,
+
+    /// Deviation from the ILS glideslope or LPV/GLS glidepath in dots. A positive value means the
+    /// aircraft is below the glideslope. `None` if no approach guidance is received or its signal
+    /// is not valid.
+    pub glideslope_deviation: Option<f64>,
+}
+
+/// Type of the approach selected by the crew
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ApproachType {
+    /// No approach is selected
+    NotSelected,
+
+    /// A precision approach, e.g. ILS, GLS or LPV
+    Precision,
+
+    /// A non-precision approach, e.g. VOR, NDB or LNAV
+    NonPrecision,
+
+    /// The input is not available to the TAWS
+    #[default]
+    NotAvailable,
+}
+
+/// Position of the flaps
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FlapPosition {
+    /// The flaps are retracted
+    Up,
+
+    /// The flaps are extended, but not in landing configuration
+    Intermediate,
+
+    /// The flaps are in landing configuration
+    Landing,
+
+    /// The input is not available to the TAWS
+    #[default]
+    NotAvailable,
+}
+
+/// Position of the landing gear
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GearPosition {
+    /// The landing gear is retracted or in transit
+    Up,
+
+    /// The landing gear is down and locked
+    Down,
+
+    /// The input is not available to the TAWS
+    #[default]
+    NotAvailable,
+}
+
+/// State of a discrete input, such as a switch or a sensor
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "use-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Discrete {
+    /// The switch is on, or the sensor detects its condition
+    Active,
+
+    /// The switch is off, or the sensor does not detect its condition
+    Inactive,
+
+    /// The input is not available to the TAWS
+    #[default]
+    NotAvailable,
+}
+
+impl fmt::Display for ApproachType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ApproachType::NotSelected => "not selected",
+            ApproachType::Precision => "precision",
+            ApproachType::NonPrecision => "non-precision",
+            ApproachType::NotAvailable => "n/a",
+        };
+        f.write_str(name)
+    }
+}
+
+impl fmt::Display for FlapPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FlapPosition::Up => "up",
+            FlapPosition::Intermediate => "intermediate",
+            FlapPosition::Landing => "landing",
+            FlapPosition::NotAvailable => "n/a",
+        };
+        f.write_str(name)
+    }
+}
+
+impl fmt::Display for GearPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            GearPosition::Up => "up",
+            GearPosition::Down => "down",
+            GearPosition::NotAvailable => "n/a",
+        };
+        f.write_str(name)
+    }
+}
+
+impl fmt::Display for Discrete {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Discrete::Active => "active",
+            Discrete::Inactive => "inactive",
+            Discrete::NotAvailable => "n/a",
+        };
+        f.write_str(name)
+    }
 }
 
 /// This configuration holds various details about the aircraft in use. These are necessary for
 /// example when estimating path trajectories for FLTA.
+///
+/// The terrain and runway sources are borrowed for `'a`, so a `Taws` can not outlive them.
 #[derive(Clone, Debug)]
-pub struct TawsConfig {
+pub struct TawsConfig<'a> {
     pub max_climbrate: Velocity,
     pub max_climbrate_change: Acceleration,
+
+    /// Height above terrain at which Mode 3 disarms after a take-off or go around
+    pub mode3_disarm_height: Length,
+
+    /// Source of the runways used for PDA, FFAC and FLTA, `None` if no runway data is available
+    pub runway_server: Option<&'a dyn RunwayServer>,
+
+    /// Source of the terrain elevation used for FLTA, `None` if no terrain data is available
+    pub terrain_server: Option<&'a dyn TerrainServer>,
+
+    /// Look-ahead time within which a violation of the required terrain clearance causes a FLTA
+    /// caution
+    pub flta_caution_time: Time,
+
+    /// Look-ahead time within which a violation of the required terrain clearance causes a FLTA
+    /// warning
+    pub flta_warning_time: Time,
+
+    /// Minimum clearance between the projected flight path and the terrain en route, reduced on
+    /// approach and further close to the runway
+    pub flta_required_clearance: Length,
+
+    /// Altitude callouts annunciated by Mode 6
+    pub mode6_callouts: &'a [Callout],
+
+    /// Decision height at which the `Minimums` callout is annunciated, `None` if no decision height
+    /// is set
+    pub decision_height: Option<Length>,
+
+    /// Height above terrain below which Mode 7 detects windshear
+    pub mode7_max_height: Length,
+
+    /// Shear factor (F-factor) at which a decreasing performance shear triggers a Mode 7 warning
+    pub mode7_decreasing_threshold: f64,
+
+    /// Shear factor (F-factor) at which an increasing performance shear triggers a Mode 7 caution,
+    /// given as a positive number
+    pub mode7_increasing_threshold: f64,
+
+    /// Pitch attitude at which the tail of the aircraft touches the ground, `None` if the tail
+    /// strike alert is not used
+    pub tail_strike_pitch_limit: Option<Angle>,
+
+    /// Whether aircraft states are checked for plausibility before they reach the alert systems
+    ///
+    /// Only meant to be disabled when testing with synthetic data, which is not physically
+    /// plausible.
+    pub signal_test: bool,
+
+    /// Whether the input freshness, the terrain database and the internal consistency of the alert
+    /// systems are monitored continuously
+    ///
+    /// Only meant to be disabled when testing with synthetic data, which does not arrive at a
+    /// steady rate.
+    pub continuous_self_test: bool,
+
+    /// Minimum time an alert stays present after its onset, even if its condition is gone
+    pub alert_min_duration: Time,
+
+    /// Time for which the condition of an alert must be absent before the alert clears or is
+    /// downgraded to a lower level, for each alert
+    pub alert_clear_delay: fn(Alert) -> Time,
+
+    /// Ranks concurrent alerts, including those of other systems of the installation
+    ///
+    /// Every alert which the alert systems may emit must be listed, which `Taws::new` checks.
+    pub alert_priorities: AlertPriorities,
 }
 
+/// Reasons why a `TawsConfig` can not be used to create a `Taws`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The alert priorities miss an alert which the alert systems may emit
+    Unprioritized {
+        alert: Alert,
+        level: AlertLevel,
+        sub_type: Option<AlertSubType>,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Unprioritized {
+                alert,
+                level,
+                sub_type: Some(sub_type),
+            } => write!(
+                f,
+                "no priority configured for {:?} {:?} ({:?})",
+                alert, level, sub_type
+            ),
+            ConfigError::Unprioritized { alert, level, .. } => {
+                write!(f, "no priority configured for {:?} {:?}", alert, level)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConfigError {}
+
 impl AircraftState {
     /// Normalizes an `AircraftState`. Only normalized `AircraftStates` should be fed to the TAWS.
     pub(crate) fn normalize(&mut self) {
@@ -90,21 +331,6 @@ impl AircraftState {
             Self::modulo(self.position_lon + half_revolution, one_revolution) - half_revolution;
     }
 
-    pub(crate) fn check(&self) {
-        let zero = Angle::new::<revolution>(0.0);
-        let one_revolution = Angle::new::<revolution>(1.0);
-        let half_revolution = Angle::new::<revolution>(0.5);
-        let quarter_revolution = Angle::new::<revolution>(0.25);
-
-        (zero..=one_revolution).contains(&self.heading);
-
-        (-half_revolution..=half_revolution).contains(&self.roll);
-        (-half_revolution..=half_revolution).contains(&self.pitch);
-
-        (-quarter_revolution..=quarter_revolution).contains(&self.position_lat);
-        (-half_revolution..=half_revolution).contains(&self.position_lon);
-    }
-
     fn modulo<T: Copy + Add<Output = T> + Rem<Output = T>>(a: T, b: T) -> T {
         ((a % b) + b) % b
     }
@@ -131,7 +357,13 @@ impl fmt::Display for AircraftState {
   speed: {speed:.2}
   pitch_angle: {pitch_angle:.2}
   roll_angle: {roll_angle:.2}
-  steep_approach: {steep_approach}\n",
+  steep_approach: {steep_approach}
+  approach: {approach}
+  flaps: {flaps}
+  landing_gear: {landing_gear}
+  weight_on_wheels: {weight_on_wheels}
+  terrain_inhibit: {terrain_inhibit}
+  glideslope_deviation: {glideslope_deviation:?}\n",
             timestamp = s.with(self.timestamp),
             altitude_sea = ft.with(self.altitude),
             altitude_ground = ft.with(self.altitude_ground),
@@ -143,15 +375,38 @@ impl fmt::Display for AircraftState {
             pitch_angle = dg.with(self.pitch),
             roll_angle = dg.with(self.roll),
             steep_approach = self.steep_approach,
+            approach = self.approach,
+            flaps = self.flaps,
+            landing_gear = self.landing_gear,
+            weight_on_wheels = self.weight_on_wheels,
+            terrain_inhibit = self.terrain_inhibit,
+            glideslope_deviation = self.glideslope_deviation,
         )
     }
 }
 
-impl Default for TawsConfig {
+impl Default for TawsConfig<'_> {
     fn default() -> Self {
         Self {
             max_climbrate: Velocity::new::<foot_per_minute>(700.0),
             max_climbrate_change: Acceleration::new::<foot_per_second_squared>(100.0),
+            mode3_disarm_height: Length::new::<foot>(1500.0),
+            runway_server: None,
+            terrain_server: None,
+            flta_caution_time: Time::new::<second>(60.0),
+            flta_warning_time: Time::new::<second>(30.0),
+            flta_required_clearance: Length::new::<foot>(700.0),
+            mode6_callouts: &Callout::ALL,
+            decision_height: None,
+            mode7_max_height: Length::new::<foot>(1500.0),
+            mode7_decreasing_threshold: 0.105,
+            mode7_increasing_threshold: 0.105,
+            tail_strike_pitch_limit: None,
+            signal_test: true,
+            continuous_self_test: true,
+            alert_min_duration: Time::new::<second>(2.0),
+            alert_clear_delay: |_| Time::new::<second>(1.0),
+            alert_priorities: AlertPriorities::DO_367,
         }
     }
 }
@@ -164,32 +419,53 @@ mod test {
 
     const EPS: f64 = 1e-10;
 
+    #[test]
+    fn inputs_not_available_by_default() {
+        let state = AircraftState::default();
+
+        assert_eq!(state.approach, ApproachType::NotAvailable);
+        assert_eq!(state.flaps, FlapPosition::NotAvailable);
+        assert_eq!(state.landing_gear, GearPosition::NotAvailable);
+        assert_eq!(state.weight_on_wheels, Discrete::NotAvailable);
+        assert_eq!(state.terrain_inhibit, Discrete::NotAvailable);
+    }
+
     #[test]
     fn negative_altitude() {
-        let mut state = AircraftState::default();
-        state.altitude_ground = Length::new::<foot>(-12.0);
+        let mut state = AircraftState {
+            altitude_ground: Length::new::<foot>(-12.0),
+            ..Default::default()
+        };
+        state.normalize();
+        assert_eq!(state.altitude_ground, Length::new::<foot>(-12.0));
     }
 
     #[test]
     fn normalize_angle_below_zero() {
-        let mut aircraft_state = AircraftState::default();
-        aircraft_state.heading = Angle::new::<degree>(-1.0);
+        let mut aircraft_state = AircraftState {
+            heading: Angle::new::<degree>(-1.0),
+            ..Default::default()
+        };
         aircraft_state.normalize();
         assert_eq!(aircraft_state.heading, Angle::new::<degree>(359.0));
     }
 
     #[test]
     fn normalize_angle_far_below_zero() {
-        let mut aircraft_state = AircraftState::default();
-        aircraft_state.heading = Angle::new::<degree>(-1024.0);
+        let mut aircraft_state = AircraftState {
+            heading: Angle::new::<degree>(-1024.0),
+            ..Default::default()
+        };
         aircraft_state.normalize();
         assert!((aircraft_state.heading - Angle::new::<degree>(56.0)).get::<degree>() < EPS);
     }
 
     #[test]
     fn normalize_angle_far_above_zero() {
-        let mut aircraft_state = AircraftState::default();
-        aircraft_state.heading = Angle::new::<degree>(1024.0);
+        let mut aircraft_state = AircraftState {
+            heading: Angle::new::<degree>(1024.0),
+            ..Default::default()
+        };
         aircraft_state.normalize();
         assert!((aircraft_state.heading - Angle::new::<degree>(304.0)).get::<degree>() < EPS);
     }
@@ -1,6 +1,13 @@
 use std::convert::Infallible;
 
-use uom::si::{f64::*, length::foot, velocity::foot_per_minute};
+use uom::si::{
+    angle::radian,
+    f64::*,
+    length::{foot, kilometer, nautical_mile},
+    ratio::ratio,
+    time::second,
+    velocity::{foot_per_minute, knot},
+};
 
 use cucumber_rust::{async_trait, given, then, when, WorldInit};
 use opentaws::prelude::*;
@@ -13,11 +20,19 @@ fn main() {
     futures::executor::block_on(runner.run());
 }
 
+type Mould = Box<dyn FnMut(&mut AircraftState)>;
+
 #[derive(WorldInit)]
 pub struct MyWorld {
-    taws: Taws,
-    moulds: Vec<Box<dyn FnMut(&mut AircraftState)>>,
+    taws: Taws<'static>,
+    moulds: Vec<Mould>,
     test_length: usize,
+    taws_state: Option<TawsState>,
+    self_test_report: Option<SelfTestReport>,
+    voice_messages: Vec<VoiceMessage>,
+    external_alerts: Vec<ExternalAlert>,
+    suppressed: Vec<Alert>,
+    height_range: Option<(Length, Length)>,
 }
 
 #[given("the plane is flying")]
@@ -50,12 +65,317 @@ fn steep_approach(world: &mut MyWorld, maybe_not: String) {
     }
 }
 
+#[given(regex = r"^non-precision approach is ?(not)? selected$")]
+fn non_precision_approach(world: &mut MyWorld, maybe_not: String) {
+    let approach = if maybe_not == "not" {
+        ApproachType::Precision
+    } else {
+        ApproachType::NonPrecision
+    };
+    world.add_mould(move |a| a.approach = approach);
+}
+
+// Must be the first step of a scenario, as it replaces the TAWS
+#[given("no runway data is available")]
+fn no_runway_data(world: &mut MyWorld) {
+    world.taws = Taws::new(TawsConfig {
+        runway_server: None,
+        ..config()
+    })
+    .unwrap();
+}
+
+// Must be the first step of a scenario, as it replaces the TAWS
+#[given("the terrain data is unavailable")]
+fn terrain_data_unavailable(world: &mut MyWorld) {
+    world.taws = Taws::new(TawsConfig {
+        terrain_server: Some(&UnavailableTerrainServer),
+        ..config()
+    })
+    .unwrap();
+}
+
+// Must be the first step of a scenario, as it replaces the TAWS
+#[given(regex = r"^the tail strike pitch limit is (\d+) degrees$")]
+fn tail_strike_pitch_limit(world: &mut MyWorld, pitch_limit: f64) {
+    world.taws = Taws::new(TawsConfig {
+        tail_strike_pitch_limit: Some(Angle::new::<degree>(pitch_limit)),
+        ..config()
+    })
+    .unwrap();
+}
+
+// Must be the first step of a scenario, as it replaces the TAWS
+#[given("the signal test is enabled")]
+fn signal_test_enabled(world: &mut MyWorld) {
+    world.taws = Taws::new(TawsConfig {
+        signal_test: true,
+        ..config()
+    })
+    .unwrap();
+}
+
+#[given(regex = r"^the flaps are ?(not)? in landing configuration$")]
+fn landing_flaps(world: &mut MyWorld, maybe_not: String) {
+    if maybe_not == "not" {
+        world.add_mould(|a| a.flaps = FlapPosition::Intermediate);
+    } else {
+        world.add_mould(|a| a.flaps = FlapPosition::Landing);
+    }
+}
+
+#[given(regex = r"^the landing gear is ?(not)? down$")]
+fn landing_gear(world: &mut MyWorld, maybe_not: String) {
+    if maybe_not == "not" {
+        world.add_mould(|a| a.landing_gear = GearPosition::Up);
+    } else {
+        world.add_mould(|a| a.landing_gear = GearPosition::Down);
+    }
+}
+
+#[given(regex = r"^the (approach type|flap position|landing gear position) is not available$")]
+fn input_not_available(world: &mut MyWorld, input: String) {
+    match input.as_str() {
+        "approach type" => world.add_mould(|a| a.approach = ApproachType::NotAvailable),
+        "flap position" => world.add_mould(|a| a.flaps = FlapPosition::NotAvailable),
+        "landing gear position" => world.add_mould(|a| a.landing_gear = GearPosition::NotAvailable),
+        _ => {
+            panic!("unable to parse this sentence");
+        }
+    }
+}
+
+#[given(regex = r"^the terrain inhibit switch is ?(not)? active$")]
+fn terrain_inhibit(world: &mut MyWorld, maybe_not: String) {
+    if maybe_not == "not" {
+        world.add_mould(|a| a.terrain_inhibit = Discrete::Inactive);
+    } else {
+        world.add_mould(|a| a.terrain_inhibit = Discrete::Active);
+    }
+}
+
+#[given(regex = r"^the glideslope is ?(not)? valid$")]
+fn glideslope_valid(world: &mut MyWorld, maybe_not: String) {
+    if maybe_not == "not" {
+        world.add_mould(|a| a.glideslope_deviation = None);
+    } else {
+        world.add_mould(|a| {
+            a.glideslope_deviation.get_or_insert(0.0);
+        });
+    }
+}
+
+#[given("the glideslope alert is cancelled")]
+fn glideslope_cancelled(world: &mut MyWorld) {
+    world.taws.glideslope_cancel();
+}
+
+#[when(regex = r"^the deviation below the glideslope is at (most|least) ([\d.]+) dots$")]
+fn glideslope_deviation(world: &mut MyWorld, most_or_least: String, dots: f64) {
+    let mut bouncer = BouncingClamp();
+    world.add_mould(move |a| {
+        let mut deviation = a.glideslope_deviation.unwrap_or_default();
+        match most_or_least.as_str() {
+            "most" => bouncer.at_most(&mut deviation, dots),
+            "least" => bouncer.at_least(&mut deviation, dots),
+            _ => {
+                panic!("unable to parse this sentence");
+            }
+        }
+        a.glideslope_deviation = Some(deviation);
+    });
+}
+
+#[given(regex = r"^the airspeed is at (most|least) (\d+) knots$")]
+fn airspeed(world: &mut MyWorld, most_or_least: String, speed: f64) {
+    let speed = Velocity::new::<knot>(speed);
+    let mut bouncer = BouncingClamp();
+    match most_or_least.as_str() {
+        "most" => {
+            world.add_mould(move |a| bouncer.at_most(&mut a.speed_air, speed));
+        }
+        "least" => {
+            world.add_mould(move |a| bouncer.at_least(&mut a.speed_air, speed));
+        }
+        _ => {
+            panic!("unable to parse this sentence");
+        }
+    }
+}
+
+#[when(regex = r"^the pitch angle is at (most|least) (\d+) degrees$")]
+fn pitch_angle(world: &mut MyWorld, most_or_least: String, pitch: f64) {
+    let mut bouncer = BouncingClamp();
+    world.add_mould(move |a| {
+        let mut actual = a.pitch.get::<degree>();
+        match most_or_least.as_str() {
+            "most" => bouncer.in_range(&mut actual, -90.0, pitch),
+            "least" => bouncer.in_range(&mut actual, pitch, 90.0),
+            _ => {
+                panic!("unable to parse this sentence");
+            }
+        }
+        a.pitch = Angle::new::<degree>(actual);
+    });
+}
+
+// The headwind is the difference between airspeed and ground speed. To change it at a steady rate,
+// one of them grows with every frame, while the other one stays at 140 knots. The aircraft flies
+// level without any updraft, and the frames are a tenth of a second apart.
+#[when(regex = r"^the headwind (increases|decreases) by (\d+) knots per second$")]
+fn headwind_change(world: &mut MyWorld, increases_or_decreases: String, rate: f64) {
+    let speed = Velocity::new::<knot>(140.0);
+    let rate = Velocity::new::<knot>(rate);
+    let mut frame = 0;
+
+    world.add_mould(move |a| {
+        let time = frame as f64 * 0.1;
+        a.timestamp = Time::new::<second>(time);
+        a.climb_rate = Velocity::new::<foot_per_minute>(0.0);
+        a.pitch = Angle::new::<radian>(0.0);
+        match increases_or_decreases.as_str() {
+            "increases" => {
+                a.speed_air = speed + rate * time;
+                a.speed_ground = speed;
+            }
+            "decreases" => {
+                a.speed_air = speed;
+                a.speed_ground = speed + rate * time;
+            }
+            _ => {
+                panic!("unable to parse this sentence");
+            }
+        }
+        frame += 1;
+    });
+}
+
+// The roll angle is bounced into a range of degrees to either side, with bank to the left being
+// negative
+#[when(regex = r"^the roll angle is at (most|least) (\d+) degrees$")]
+fn roll_angle(world: &mut MyWorld, most_or_least: String, roll: f64) {
+    let mut bouncer = BouncingClamp();
+    world.add_mould(move |a| {
+        let mut actual = a.roll.get::<degree>();
+        match most_or_least.as_str() {
+            "most" => bouncer.in_range(&mut actual, -roll, roll),
+            "least" => bouncer.in_range(&mut actual, roll, 180.0),
+            _ => {
+                panic!("unable to parse this sentence");
+            }
+        }
+        a.roll = Angle::new::<degree>(actual);
+    });
+}
+
 #[then(regex = r"^(.+) shall be armed$")]
 fn shall_be_armed(world: &mut MyWorld, alert: AlertWrapper) {
     // TODO check if space needs to be removed
     assert!(world.taws.is_armed(alert.into()));
 }
 
+#[then(regex = r"^(.+) shall not be armed$")]
+fn shall_not_be_armed(world: &mut MyWorld, alert: AlertWrapper) {
+    assert!(!world.taws.is_armed(alert.into()));
+}
+
+#[when("the aircraft takes off")]
+fn takes_off(world: &mut MyWorld) {
+    let ground_speed = Velocity::new::<knot>(140.0);
+    let climb_rate = Velocity::new::<foot_per_minute>(1500.0);
+
+    // roll on the runway for some seconds, then rotate and climb to 100 feet
+    for i in 0..300 {
+        let time = Time::new::<second>(i as f64 * 0.1);
+        let rotation = Time::new::<second>(20.0);
+        let frame = if time < rotation {
+            AircraftState {
+                timestamp: time,
+                speed_ground: ground_speed * (time / rotation),
+                ..Default::default()
+            }
+        } else {
+            AircraftState {
+                timestamp: time,
+                altitude: climb_rate * (time - rotation),
+                altitude_ground: climb_rate * (time - rotation),
+                climb_rate,
+                speed_ground: ground_speed,
+                ..Default::default()
+            }
+        };
+        world.process(&frame);
+    }
+}
+
+#[when(regex = r"^the aircraft climbs from (\d+) to (\d+) feet above terrain$")]
+fn climbs(world: &mut MyWorld, from: f64, to: f64) {
+    let climb_rate = Velocity::new::<foot_per_minute>(1500.0);
+    let from = Length::new::<foot>(from);
+    let to = Length::new::<foot>(to);
+    let dt = Time::new::<second>(0.1);
+
+    let mut frame = AircraftState {
+        altitude: from,
+        altitude_ground: from,
+        climb_rate,
+        speed_ground: Velocity::new::<knot>(140.0),
+        ..Default::default()
+    };
+    while frame.altitude_ground <= to {
+        world.process(&frame);
+        frame.timestamp += dt;
+        frame.altitude += climb_rate * dt;
+        frame.altitude_ground += climb_rate * dt;
+    }
+}
+
+#[when(regex = r"^the aircraft descends from (\d+) to (\d+) feet above terrain$")]
+fn descends(world: &mut MyWorld, from: f64, to: f64) {
+    let climb_rate = Velocity::new::<foot_per_minute>(-1000.0);
+    let from = Length::new::<foot>(from);
+    let to = Length::new::<foot>(to);
+    let dt = Time::new::<second>(0.1);
+
+    let mut frame = AircraftState {
+        altitude: from,
+        altitude_ground: from,
+        climb_rate,
+        speed_ground: Velocity::new::<knot>(140.0),
+        ..Default::default()
+    };
+    while frame.altitude_ground >= to {
+        world.process(&frame);
+        frame.timestamp += dt;
+        frame.altitude += climb_rate * dt;
+        frame.altitude_ground += climb_rate * dt;
+    }
+}
+
+#[when(regex = r"^the altitude loss is at (most|least) (\d+) feet$")]
+fn altitude_loss(world: &mut MyWorld, most_or_least: String, loss: f64) {
+    let loss = Length::new::<foot>(loss);
+    let no_loss = Length::new::<foot>(0.0);
+    let mut bouncer = BouncingClamp();
+    let mut reference = None;
+
+    // the first frame defines the altitude from which all following frames lose altitude
+    world.add_mould(move |a| match reference {
+        None => reference = Some(a.altitude),
+        Some(reference) => {
+            let mut actual_loss = (a.altitude - reference).abs();
+            match most_or_least.as_str() {
+                "most" => bouncer.in_range(&mut actual_loss, no_loss, loss),
+                "least" => bouncer.at_least(&mut actual_loss, loss),
+                _ => {
+                    panic!("unable to parse this sentence");
+                }
+            }
+            a.altitude = reference - actual_loss;
+        }
+    });
+}
+
 #[when(regex = r"^the rate of descent is at (most|least) (\d+) feet per minute$")]
 fn rate_of_descent(world: &mut MyWorld, most_or_least: String, rod: f64) {
     let rod = Velocity::new::<foot_per_minute>(rod);
@@ -88,12 +408,139 @@ fn height_above_terrain(world: &mut MyWorld, maybe_not: String, lower: f64, uppe
             bouncer.not_in_range(&mut a.altitude_ground, height_at_least, height_at_most)
         });
     } else {
+        world.height_range = Some((height_at_least, height_at_most));
         world.add_mould(move |a| {
             bouncer.in_range(&mut a.altitude_ground, height_at_least, height_at_most)
         }); // TODO altitude or altitude_ground
     }
 }
 
+// The runway of the runway server used in the tests is at sea level. For the height above terrain
+// the terrain is at sea level as well, while for the height above the nearest runway elevation the
+// terrain lies well below the runway, so that both heights differ, and the aircraft is kept at the
+// runway.
+#[given(
+    regex = r"^the height above (terrain|nearest runway elevation) is greater or equal to (\d+) foot$"
+)]
+fn height_at_least(world: &mut MyWorld, reference: String, height: f64) {
+    let height = Length::new::<foot>(height);
+    let mut bouncer = BouncingClamp();
+
+    world.add_mould(move |a| {
+        let mut actual = a.altitude;
+        bouncer.at_least(&mut actual, height);
+        set_height(a, &reference, actual);
+    });
+}
+
+// Every second frame descends below the given height, so that each of them crosses it. The frames
+// are half a second apart.
+#[when(regex = r"^the height above (terrain|nearest runway elevation) is lower than (\d+) foot$")]
+fn height_crossing(world: &mut MyWorld, reference: String, height: f64) {
+    let height = Length::new::<foot>(height);
+    let lowest = Length::new::<foot>(0.0);
+    let mut bouncer = BouncingClamp();
+    let mut frame = 0;
+
+    world.add_mould(move |a| {
+        a.timestamp = Time::new::<second>(frame as f64 * 0.5);
+        if frame % 2 == 1 {
+            let mut actual = a.altitude;
+            bouncer.in_range(&mut actual, lowest, height - Length::new::<foot>(1.0));
+            set_height(a, &reference, actual);
+        }
+        frame += 1;
+    });
+}
+
+fn set_height(a: &mut AircraftState, reference: &str, height: Length) {
+    a.altitude = height;
+    a.altitude_ground = match reference {
+        "terrain" => height,
+        _ => height + Length::new::<foot>(1000.0),
+    };
+    if reference != "terrain" {
+        a.position_lat = Angle::new::<degree>(0.0);
+        a.position_lon = Angle::new::<degree>(0.0);
+    }
+}
+
+// The closure rate is derived by the TAWS from successive frames. Hence this mould chooses the
+// timestamp of each frame, so that the change in height above terrain since the last frame matches
+// the closure rate. It must be applied after the height above terrain was set.
+//
+// The TAWS filters the closure rate, so a closure rate of at least the given one needs a steady
+// descent: the aircraft descends through the range of the height above terrain at up to twice the
+// closure rate, and starts over at its top after a gap in the data, which restarts the closure rate.
+#[when(regex = r"^the terrain closure rate is at (most|least) (\d+) feet per minute$")]
+fn terrain_closure_rate(world: &mut MyWorld, most_or_least: String, closure_rate: f64) {
+    let closure_rate = Velocity::new::<foot_per_minute>(closure_rate);
+    let min_closure_rate = Velocity::new::<foot_per_minute>(1.0);
+    let frame_time = Time::new::<second>(0.1);
+    let data_gap = Time::new::<second>(1.5);
+    let mut bouncer = BouncingClamp();
+    let mut last_frame: Option<(Time, Length)> = None;
+
+    match most_or_least.as_str() {
+        "most" => world.add_mould(move |a| {
+            let mut rate = a.climb_rate.abs();
+            bouncer.in_range(&mut rate, min_closure_rate, closure_rate);
+
+            a.timestamp = match last_frame {
+                Some((last_timestamp, last_height)) if a.altitude_ground < last_height => {
+                    last_timestamp + (last_height - a.altitude_ground) / rate
+                }
+                // the aircraft is not approaching the terrain, so there is no closure rate to adjust
+                Some((last_timestamp, _)) => last_timestamp + frame_time,
+                None => Time::new::<second>(0.0),
+            };
+            last_frame = Some((a.timestamp, a.altitude_ground));
+        }),
+        "least" => {
+            let (lowest, highest) = world
+                .height_range
+                .expect("the height above terrain is set before the closure rate");
+            world.add_mould(move |a| {
+                let mut rate = a.climb_rate.abs();
+                bouncer.in_range(&mut rate, closure_rate, closure_rate * 2.0);
+
+                let (timestamp, height) = match last_frame {
+                    Some((last_timestamp, last_height))
+                        if last_height - rate * frame_time >= lowest =>
+                    {
+                        (last_timestamp + frame_time, last_height - rate * frame_time)
+                    }
+                    Some((last_timestamp, _)) => (last_timestamp + data_gap, highest),
+                    None => (Time::new::<second>(0.0), highest),
+                };
+                a.timestamp = timestamp;
+                a.altitude_ground = height;
+                last_frame = Some((timestamp, height));
+            })
+        }
+        _ => {
+            panic!("unable to parse this sentence");
+        }
+    }
+}
+
+// The runway server used in the tests knows only one runway at 0° north and 0° east, so the
+// aircraft is placed on the prime meridian north of it.
+#[when(regex = r"^the nearest runway is between (\d+) and (\d+) nautical miles away$")]
+fn nearest_runway_distance(world: &mut MyWorld, lower: f64, upper: f64) {
+    let earth_radius = Length::new::<kilometer>(6371.0);
+    let distance_at_least = Length::new::<nautical_mile>(lower);
+    let distance_at_most = Length::new::<nautical_mile>(upper);
+    let mut bouncer = BouncingClamp();
+
+    world.add_mould(move |a| {
+        let mut distance = Length::new::<nautical_mile>(a.position_lat.get::<degree>());
+        bouncer.in_range(&mut distance, distance_at_least, distance_at_most);
+        a.position_lat = Angle::new::<radian>((distance / earth_radius).get::<ratio>());
+        a.position_lon = Angle::new::<degree>(0.0);
+    });
+}
+
 #[then(regex = "^a (.*) alert is not emitted at all$")]
 fn is_not_emitted(world: &mut MyWorld, alert_and_level: AlertAndLevelWrapper) {
     let (alert, level) = alert_and_level.into();
@@ -109,7 +556,8 @@ fn is_not_emitted(world: &mut MyWorld, alert_and_level: AlertAndLevelWrapper) {
     }
 
     for frame in aircraft_states {
-        let alert_state = world.taws.process(&frame);
+        let taws_state = world.process(&frame);
+        let alert_state = taws_state.alert_state();
         if alert_state.iter().any(|(a, l)| a == alert && l <= level) {
             panic!(
                 "Aicraft state that violated the scenario: {:#?}\nalerts emitted: {:#?}",
@@ -118,32 +566,36 @@ fn is_not_emitted(world: &mut MyWorld, alert_and_level: AlertAndLevelWrapper) {
         }
     }
 }
-#[then(regex = r"^a (.*) alert is emitted within (\d+) seconds$")]
-fn is_emitted_within(world: &mut MyWorld, alert_and_level: AlertAndLevelWrapper, _seconds: f64) {
+// The frames are a tenth of a second apart, unless a mould chooses the timestamps. The alert must
+// never be absent for longer than the given time, measured from the first frame or from the last
+// frame in which it was present.
+#[then(regex = r"^a (.*) alert is emitted within ([\d.]+) seconds$")]
+fn is_emitted_within(world: &mut MyWorld, alert_and_level: AlertAndLevelWrapper, seconds: f64) {
     let (alert, level) = alert_and_level.into();
+    let max_delay = Time::new::<second>(seconds);
     let mut aircraft_states: Vec<_> = AircraftStateGenerator::default()
         .take(world.test_length)
         .collect();
 
     // press the test data in our moulds
-    for frame in aircraft_states.iter_mut() {
+    for (i, frame) in aircraft_states.iter_mut().enumerate() {
+        frame.timestamp = Time::new::<second>(i as f64 * 0.1);
         for f in world.moulds.iter_mut() {
             f(frame);
         }
     }
 
+    let mut last_emitted = None;
     for frame in aircraft_states {
-        let alert_state = world.taws.process(&frame);
-        // TODO what about the time constraint?
-        // Count all alerts that are from the functionality Mode1 and are of higher or
-        // same priority as `level`. If the count is 0, the system did not alert
-        // appropiately.
-        if alert_state
-            .iter()
-            .filter(|(a, l)| *a == alert && *l <= level)
-            .count()
-            == 0
-        {
+        let taws_state = world.process(&frame);
+        let alert_state = taws_state.alert_state();
+        if alert_state.iter().any(|(a, l)| a == alert && l <= level) {
+            let timing = taws_state
+                .timing(alert)
+                .expect("present alerts have a timing");
+            assert!(timing.onset <= frame.timestamp && timing.cleared.is_none());
+            last_emitted = Some(frame.timestamp);
+        } else if frame.timestamp - *last_emitted.get_or_insert(frame.timestamp) > max_delay {
             panic!(
                 "Aicraft state that violated the scenario: {:#?}\nalerts emitted: {:#?}",
                 frame, alert_state
@@ -152,6 +604,280 @@ fn is_emitted_within(world: &mut MyWorld, alert_and_level: AlertAndLevelWrapper,
     }
 }
 
+#[then(regex = r#"^the voice message "(.+)" is ?(not)? played$"#)]
+fn voice_message_played(world: &mut MyWorld, message: String, maybe_not: String) {
+    let played = world
+        .voice_messages
+        .iter()
+        .any(|voice_message| voice_message.to_string() == message);
+    assert_eq!(played, maybe_not != "not");
+}
+
+#[given("an alert can occur concurrently")]
+fn alert_occurs_concurrently(_world: &mut MyWorld) {}
+
+#[then("that alert has a priority")]
+fn alert_has_priority(_world: &mut MyWorld) {
+    let priorities = config().alert_priorities;
+    for alert in Alert::ALL {
+        for (level, sub_type) in alert.outputs() {
+            assert!(priorities.priority(alert, *level, *sub_type).is_some());
+        }
+    }
+}
+
+#[then(regex = r"^a TAWS with alert priorities missing (.+) refuses to start$")]
+fn missing_priority(_world: &mut MyWorld, alert: AlertWrapper) {
+    let alert: Alert = alert.into();
+    let list: Vec<_> = AlertPriorities::DO_367
+        .iter()
+        .filter(|entry| !matches!(entry, PrioritizedAlert::Taws(a, _, _) if *a == alert))
+        .collect();
+    let config = TawsConfig {
+        alert_priorities: AlertPriorities::new(Box::leak(list.into_boxed_slice())),
+        ..config()
+    };
+
+    match Taws::new(config) {
+        Err(ConfigError::Unprioritized { alert: missing, .. }) => assert_eq!(missing, alert),
+        Ok(_) => panic!("TAWS started without a priority for {:?}", alert),
+    }
+}
+
+#[given("a TCAS RA is active")]
+fn tcas_ra_active(world: &mut MyWorld) {
+    world
+        .external_alerts
+        .push(ExternalAlert::TcasResolutionAdvisory);
+}
+
+#[given("a TCAS TA is active")]
+fn tcas_ta_active(world: &mut MyWorld) {
+    world
+        .external_alerts
+        .push(ExternalAlert::TcasTrafficAdvisory);
+}
+
+#[given(regex = r"^an external alert of priority (\d+) is active$")]
+fn other_external_alert_active(world: &mut MyWorld, priority: u8) {
+    world.external_alerts.push(ExternalAlert::Other(priority));
+}
+
+#[then(regex = r"^(.+) is ?(not)? suppressed$")]
+fn is_suppressed(world: &mut MyWorld, alert: AlertWrapper, maybe_not: String) {
+    let alert: Alert = alert.into();
+    assert_eq!(world.suppressed.contains(&alert), maybe_not != "not");
+}
+
+#[then(regex = r"^the flight phase is (.+)$")]
+fn flight_phase_is(world: &mut MyWorld, phase: String) {
+    assert_eq!(world.taws.flight_phase().to_string(), phase);
+}
+
+// A plausible aircraft state is processed first, so that the next one can be compared to it
+fn process_reference(world: &mut MyWorld) -> AircraftState {
+    let reference = AircraftState {
+        timestamp: Time::new::<second>(1000.0),
+        altitude: Length::new::<foot>(1000.0),
+        altitude_ground: Length::new::<foot>(1000.0),
+        speed_air: Velocity::new::<knot>(140.0),
+        speed_ground: Velocity::new::<knot>(140.0),
+        ..Default::default()
+    };
+    assert!(world.process(&reference).input_faults().is_empty());
+    reference
+}
+
+#[when(regex = r"^an aircraft state (.+) is processed$")]
+fn process_changed(world: &mut MyWorld, change: String) {
+    let reference = process_reference(world);
+
+    let mut state = AircraftState {
+        timestamp: reference.timestamp + Time::new::<second>(1.0),
+        ..reference.clone()
+    };
+    match change.as_str() {
+        "one second later" => {}
+        "from the past" => state.timestamp = Time::new::<second>(0.0),
+        "below the terrain" => state.altitude_ground = Length::new::<foot>(-10.0),
+        "2000 feet higher one second later" => state.altitude += Length::new::<foot>(2000.0),
+        "100 knots faster one second later" => state.speed_air += Velocity::new::<knot>(100.0),
+        _ => {
+            panic!("unable to parse this sentence");
+        }
+    }
+
+    world.taws_state = Some(world.process(&state));
+}
+
+#[when(regex = r"^the rate of input data (reduces|stagnates)$")]
+fn input_rate_changes(world: &mut MyWorld, change: String) {
+    let reference = process_reference(world);
+
+    let interval = match change.as_str() {
+        "reduces" => 5.0,
+        "stagnates" => 0.0,
+        _ => unreachable!(),
+    };
+    let state = AircraftState {
+        timestamp: reference.timestamp + Time::new::<second>(interval),
+        ..reference
+    };
+
+    world.taws_state = Some(world.process(&state));
+}
+
+#[then(regex = r"^the aircraft state is rejected as (.+)$")]
+fn is_rejected(world: &mut MyWorld, reason: String) {
+    let taws_state = world
+        .taws_state
+        .as_ref()
+        .expect("no aircraft state was processed");
+    assert!(
+        taws_state
+            .input_faults()
+            .iter()
+            .any(|fault| fault.to_string() == reason),
+        "aircraft state was not rejected as {}: {:#?}",
+        reason,
+        taws_state
+    );
+}
+
+#[then(regex = r"^(.+) is ?(not)? skipped$")]
+fn is_skipped(world: &mut MyWorld, alert: AlertWrapper, maybe_not: String) {
+    let taws_state = world
+        .taws_state
+        .as_ref()
+        .expect("no aircraft state was processed");
+    let status = taws_state.function(alert.into());
+    assert_eq!(status.skipped, maybe_not != "not", "{:#?}", taws_state);
+}
+
+#[then(regex = r"^(TERR FAIL|TAWS INOP) is ?(not)? annunciated$")]
+fn is_annunciated(world: &mut MyWorld, annunciation: String, maybe_not: String) {
+    let taws_state = world
+        .taws_state
+        .as_ref()
+        .expect("no aircraft state was processed");
+    let annunciated = match annunciation.as_str() {
+        "TERR FAIL" => taws_state.terrain_failed(),
+        "TAWS INOP" => taws_state.taws_inoperative(),
+        _ => unreachable!(),
+    };
+    assert_eq!(annunciated, maybe_not != "not", "{:#?}", taws_state);
+}
+
+// Must be the first step of a scenario, as it replaces the TAWS
+#[when("a failure is detected by the continuos monitoring")]
+fn continuous_monitoring_failure(world: &mut MyWorld) {
+    world.taws = Taws::new(TawsConfig {
+        terrain_server: Some(&UnavailableTerrainServer),
+        continuous_self_test: true,
+        ..config()
+    })
+    .unwrap();
+    world.taws_state = Some(world.process(&AircraftState::default()));
+}
+
+#[then("the TAWS reports the failure")]
+fn reports_failure(world: &mut MyWorld) {
+    let taws_state = world
+        .taws_state
+        .as_ref()
+        .expect("no aircraft state was processed");
+    assert!(
+        !taws_state.self_test_failures().is_empty(),
+        "{:#?}",
+        taws_state
+    );
+}
+
+#[then("disarms all affected functions")]
+fn disarms_affected(world: &mut MyWorld) {
+    let taws_state = world
+        .taws_state
+        .as_ref()
+        .expect("no aircraft state was processed");
+    let failures = taws_state.self_test_failures();
+    let mut affected = taws_state
+        .functions()
+        .filter(|(alert, _)| failures.affect(*alert))
+        .peekable();
+
+    assert!(affected.peek().is_some(), "{:#?}", taws_state);
+    for (alert, status) in affected {
+        assert!(!world.taws.is_armed(alert), "{:?} is still armed", alert);
+        assert!(!status.armed && status.failed, "{:#?}", status);
+    }
+}
+
+#[given("the self-test is initiated")]
+fn self_test_initiated(world: &mut MyWorld) {
+    world.self_test_report = Some(world.taws.self_test());
+}
+
+#[when("the self-test is initiated")]
+fn initiate_self_test(world: &mut MyWorld) {
+    world.self_test_report = Some(world.taws.self_test());
+}
+
+// Replaces the TAWS, to initiate the self-test again with a failed terrain database
+#[when("the initiated self-test detects a failure")]
+fn initiated_self_test_failure(world: &mut MyWorld) {
+    world.taws = Taws::new(TawsConfig {
+        terrain_server: Some(&UnavailableTerrainServer),
+        ..config()
+    })
+    .unwrap();
+    world.self_test_report = Some(world.taws.self_test());
+}
+
+#[then(regex = r"^the TAWS must verify system operation and integrity of (.+)$")]
+fn verifies_capability(world: &mut MyWorld, capability: String) {
+    let report = world
+        .self_test_report
+        .as_ref()
+        .expect("no self-test was initiated");
+    match report
+        .iter()
+        .find(|(step, _)| step.to_string() == capability)
+    {
+        Some((_, passed)) => assert!(passed, "{:#?}", report),
+        None => panic!("{} was not tested: {:#?}", capability, report),
+    }
+}
+
+#[then("the failure must be reported")]
+fn failure_reported(world: &mut MyWorld) {
+    let report = world
+        .self_test_report
+        .as_ref()
+        .expect("no self-test was initiated");
+    assert!(!report.passed(), "{:#?}", report);
+
+    // the failure persists, so it is reported with every processed aircraft state
+    let taws_state = world
+        .taws
+        .process(&AircraftState::default(), &world.external_alerts);
+    for step in report.failures() {
+        assert!(taws_state
+            .self_test_failures()
+            .contains(SelfTestFailure::Initiated(step)));
+    }
+}
+
+// The random aircraft states are not physically plausible and do not arrive at a steady rate, so
+// the signal test and the continuous self-test are disabled unless a scenario enables them
+fn config() -> TawsConfig<'static> {
+    TawsConfig {
+        runway_server: Some(&NullIslandRunwayServer),
+        signal_test: false,
+        continuous_self_test: false,
+        ..Default::default()
+    }
+}
+
 // Brot und Butter implementations
 #[async_trait(?Send)]
 impl cucumber_rust::World for MyWorld {
@@ -159,9 +885,15 @@ impl cucumber_rust::World for MyWorld {
 
     async fn new() -> Result<Self, Infallible> {
         Ok(Self {
-            taws: Taws::new(Default::default()),
+            taws: Taws::new(config()).unwrap(),
             moulds: Vec::new(),
             test_length: 10000, // TODO is this a good number?
+            taws_state: None,
+            self_test_report: None,
+            voice_messages: Vec::new(),
+            external_alerts: Vec::new(),
+            suppressed: Vec::new(),
+            height_range: None,
         })
     }
 }
@@ -170,6 +902,19 @@ impl MyWorld {
     pub fn add_mould<F: 'static + FnMut(&mut AircraftState)>(&mut self, f: F) {
         self.moulds.push(Box::new(f));
     }
+
+    /// Processes an aircraft state with the active external alerts, recording the voice messages
+    /// started and the alerts suppressed by it
+    pub fn process(&mut self, state: &AircraftState) -> TawsState {
+        let taws_state = self.taws.process(state, &self.external_alerts);
+        self.voice_messages.extend(taws_state.voice_message());
+        for alert in taws_state.alert_state().suppressed() {
+            if !self.suppressed.contains(&alert) {
+                self.suppressed.push(alert);
+            }
+        }
+        taws_state
+    }
 }
 
 impl std::fmt::Debug for MyWorld {
@@ -8,7 +8,12 @@ use rand::RngCore;
 
 use uom::{
     num::Signed,
-    si::{f64::*, length::foot, time::second, velocity::foot_per_minute},
+    si::{
+        f64::*,
+        length::{foot, meter},
+        time::second,
+        velocity::foot_per_minute,
+    },
 };
 
 use opentaws::prelude::*;
@@ -31,6 +36,27 @@ impl<'a> Arbitrary<'a> for AircraftStateWrapper {
             pitch: Angle::new::<degree>(<i32 as Arbitrary>::arbitrary(u)? as f64),
             roll: Angle::new::<degree>(<i32 as Arbitrary>::arbitrary(u)? as f64),
             steep_approach: u.arbitrary()?,
+            approach: *u.choose(&[
+                ApproachType::NotSelected,
+                ApproachType::Precision,
+                ApproachType::NonPrecision,
+                ApproachType::NotAvailable,
+            ])?,
+            flaps: *u.choose(&[
+                FlapPosition::Up,
+                FlapPosition::Intermediate,
+                FlapPosition::Landing,
+                FlapPosition::NotAvailable,
+            ])?,
+            landing_gear: *u.choose(&[
+                GearPosition::Up,
+                GearPosition::Down,
+                GearPosition::NotAvailable,
+            ])?,
+            // inputs which put the aircraft on ground or inhibit alerts are left to the scenarios
+            weight_on_wheels: *u.choose(&[Discrete::Inactive, Discrete::NotAvailable])?,
+            terrain_inhibit: *u.choose(&[Discrete::Inactive, Discrete::NotAvailable])?,
+            glideslope_deviation: <Option<i32> as Arbitrary>::arbitrary(u)?.map(f64::from),
         }))
     }
 
@@ -39,6 +65,36 @@ impl<'a> Arbitrary<'a> for AircraftStateWrapper {
     }
 }
 
+/// A `RunwayServer` which only knows a single runway, located at 0° north and 0° east
+#[derive(Debug)]
+pub struct NullIslandRunwayServer;
+
+impl RunwayServer for NullIslandRunwayServer {
+    fn nearest_runway(&self, _position: &Position) -> Result<Option<Runway>, TerrainError> {
+        Ok(Some(Runway {
+            airport: Identifier::new("NULL").unwrap(),
+            designator: Identifier::new("36").unwrap(),
+            location: Position {
+                latitude: Angle::new::<degree>(0.0),
+                longitude: Angle::new::<degree>(0.0),
+                altitude_sea: Length::new::<foot>(0.0),
+            },
+            length: Length::new::<meter>(3000.0),
+            azimuth: Angle::new::<degree>(0.0),
+        }))
+    }
+}
+
+/// A `TerrainServer` which failed, it never provides an elevation
+#[derive(Debug)]
+pub struct UnavailableTerrainServer;
+
+impl TerrainServer for UnavailableTerrainServer {
+    fn elevation(&self, _position: &Position) -> Result<Length, TerrainError> {
+        Err(TerrainError::Unavailable)
+    }
+}
+
 // Parser magic
 pub struct AlertWrapper(Alert);
 impl FromStr for AlertWrapper {
@@ -47,6 +103,7 @@ impl FromStr for AlertWrapper {
         let mut input_word = s.to_lowercase();
         input_word.retain(|c| !c.is_whitespace());
         Ok(Self(match input_word.as_str() {
+            "bankangle" => Alert::BankAngle,
             "ffac" => Alert::Ffac,
             "flta" => Alert::Flta,
             "mode1" => Alert::Mode1,
@@ -54,16 +111,19 @@ impl FromStr for AlertWrapper {
             "mode3" => Alert::Mode3,
             "mode4" => Alert::Mode4,
             "mode5" => Alert::Mode5,
+            "mode6" => Alert::Mode6,
+            "mode7" => Alert::Mode7,
             "pda" => Alert::Pda,
+            "tailstrike" => Alert::TailStrike,
             _ => {
                 panic!("unable to convert {} into a variant of `Alert`", s);
             }
         }))
     }
 }
-impl Into<Alert> for AlertWrapper {
-    fn into(self) -> Alert {
-        self.0
+impl From<AlertWrapper> for Alert {
+    fn from(wrapper: AlertWrapper) -> Self {
+        wrapper.0
     }
 }
 
@@ -94,9 +154,9 @@ impl FromStr for AlertAndLevelWrapper {
         Ok(Self(alert.0, level.0))
     }
 }
-impl Into<(Alert, AlertLevel)> for AlertAndLevelWrapper {
-    fn into(self) -> (Alert, AlertLevel) {
-        (self.0, self.1)
+impl From<AlertAndLevelWrapper> for (Alert, AlertLevel) {
+    fn from(wrapper: AlertAndLevelWrapper) -> Self {
+        (wrapper.0, wrapper.1)
     }
 }
 
@@ -119,7 +179,7 @@ impl Iterator for AircraftStateGenerator {
         while buf.len() < bytes_needed {
             buf.extend_from_slice(&self.0.next_u64().to_le_bytes());
         }
-        let mut u = Unstructured::new(&mut buf);
+        let mut u = Unstructured::new(&buf);
 
         Some(AircraftStateWrapper::arbitrary(&mut u).unwrap().0) // the unwrap is safe, we guarantee that enough bytes are available
     }
@@ -197,7 +257,7 @@ impl Abs for f64 {
     }
 }
 
-impl<D: ?Sized, U: ?Sized, V: ?Sized> Abs for uom::si::Quantity<D, U, V>
+impl<D: ?Sized, U: ?Sized, V> Abs for uom::si::Quantity<D, U, V>
 where
     D: uom::si::Dimension,
     U: uom::si::Units<V>,